@@ -8,10 +8,13 @@ import { useProgram } from "@/lib/useProgram"
 import { toast } from "sonner"
 import { PublicKey } from "@solana/web3.js"
 import { useAnchorWallet } from "@solana/wallet-adapter-react"
-import * as anchor from "@coral-xyz/anchor"
 import {
-  payoutWinner,
-  fulfillRandomness,
+  payout as payoutLottery,
+  claimPrize,
+  closeSales,
+  recordSlotHash,
+  revealRandomness,
+  hasLotterySecret,
   getLotteryByPda,
   formatDuration,
 } from "@/lib/transactions"
@@ -27,9 +30,15 @@ interface LotteryData {
   ticketPrice: string
   ticketsSold: number
   totalPrizePool: string
+  state: string
+  winners: number[]
   winner: number | null
   duration: string
-  state?: string // Add state field for compatibility
+  revealSlot: string
+  revealSlotHashRecorded: boolean
+  revealDeadline: string
+  feesPaid: boolean
+  claimedTiers: number
 }
 
 // formatTime is now imported as formatDuration from transactions
@@ -136,11 +145,37 @@ const declareWinner = async () => {
       return;
     }
 
-    // Generate randomness array
-    const randomness = Array.from({ length: 32 }, () => Math.floor(Math.random() * 256));
+    // Sales of a sold-out lottery close with its last ticket
+    let closed = lottery;
+    if (lottery.state === "open") {
+      await closeSales(program, new PublicKey(id), wallet);
+      const refreshed = await getLotteryByPda(program, new PublicKey(id));
+      if (!refreshed) throw new Error("Lottery not found");
+      closed = refreshed;
+    }
+
+    if (now > parseInt(closed.revealDeadline)) {
+      toast.error("The reveal deadline has passed, the lottery can only be cancelled");
+      return;
+    }
 
-    // Call fulfillRandomness
-    const tx = await fulfillRandomness(program, new PublicKey(id), wallet, randomness);
+    // The secret is mixed with the hash of the reveal slot, so wait for it to pass
+    const revealSlot = parseInt(closed.revealSlot);
+    while ((await program.provider.connection.getSlot("confirmed")) <= revealSlot) {
+      await new Promise(resolve => setTimeout(resolve, 400));
+    }
+
+    // Without the secret, keep the slot hash so the creator's browser can still reveal later
+    if (!hasLotterySecret(new PublicKey(id))) {
+      if (!closed.revealSlotHashRecorded) {
+        await recordSlotHash(program, new PublicKey(id), wallet);
+      }
+      toast.error("Reveal the secret from the browser that created this lottery before the deadline");
+      await fetchLottery();
+      return;
+    }
+
+    const tx = await revealRandomness(program, new PublicKey(id), wallet);
     toast.success("Winner declared successfully!");
     console.log("Winner tx:", tx);
 
@@ -159,40 +194,25 @@ const declareWinner = async () => {
     if (!platformFeeAccount) return console.error("Platform fee account not set")
 
     try {
-      // Derive the winner ticket PDA based on lottery and winner ticket number
       const lotteryPda = new PublicKey(id);
-      const winnerTicketNumber = lotteryData.winner; // This should be the ticket number
-      
-      const [winnerTicketPda] = PublicKey.findProgramAddressSync(
-        [
-          Buffer.from("ticket"),
-          lotteryPda.toBuffer(),
-          new anchor.BN(winnerTicketNumber).toArrayLike(Buffer, "le", 4),
-        ],
-        program.programId
-      );
-
-      // Get the actual winner's public key from the ticket account
-      const winnerTicketAccount = await program.provider.connection.getAccountInfo(winnerTicketPda);
-      if (!winnerTicketAccount) {
-        throw new Error("Winner ticket account not found");
-      }
 
-      // Parse the ticket to get the winner's public key
-      // The player public key is at offset 8 + 32 = 40 in the ticket account
-      const winnerPublicKey = new PublicKey(winnerTicketAccount.data.slice(40, 72));
-
-      const tx = await payoutWinner(
-        program,
-        lotteryPda,
-        wallet,
-        winnerTicketPda,
-        winnerPublicKey,
-        new PublicKey(lotteryData.authority),
-        new PublicKey(platformFeeAccount)
-      )
+      // Settle the creator and platform shares, then push each unclaimed prize to its winner
+      if (!lotteryData.feesPaid) {
+        const tx = await payoutLottery(
+          program,
+          lotteryPda,
+          wallet,
+          new PublicKey(lotteryData.authority),
+          new PublicKey(platformFeeAccount)
+        )
+        console.log("Payout tx:", tx)
+      }
+      for (let tier = 0; tier < lotteryData.winners.length; tier++) {
+        if (lotteryData.claimedTiers & (1 << tier)) continue
+        const tx = await claimPrize(program, lotteryPda, wallet, tier)
+        console.log(`Tier ${tier} claim tx:`, tx)
+      }
   toast.success("Winner paid out successfully!")
-      console.log("Payout tx:", tx)
   // mark payout as done in UI
   setPayoutDone(true)
       // Refresh lottery data after payout
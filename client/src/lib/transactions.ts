@@ -1,14 +1,7 @@
 import { useProgram } from "./useProgram";
 import * as anchor from "@coral-xyz/anchor";
 import { AnchorWallet } from "@solana/wallet-adapter-react";
-import { LAMPORTS_PER_SOL, PublicKey, SystemProgram, SendTransactionError, Connection, Transaction, ConfirmOptions } from "@solana/web3.js";
-
-// Account sizes based on current struct definitions
-// Layout (bytes): 8 discriminator + 32 authority + 8 lottery_id + 8 ticket_price + 4 max_tickets
-// + 4 tickets_sold + 8 total_prize_pool + 1 option + 4 winner + 8 created_at + 8 duration
-// + 1 randomness_fulfilled + 1 bump = 87 (+ padding if any). Use 95 original to be safe.
-const LOTTERY_ACCOUNT_SIZE = 95;
-const TICKET_ACCOUNT_SIZE = 85;
+import { LAMPORTS_PER_SOL, PublicKey, SystemProgram, SendTransactionError, Connection, Transaction, ConfirmOptions, SYSVAR_SLOT_HASHES_PUBKEY } from "@solana/web3.js";
 
 // Transaction retry configuration
 const MAX_RETRIES = 3;
@@ -181,156 +174,89 @@ interface ParsedLottery {
   maxTickets: number;
   ticketsSold: number;
   totalPrizePool: string;
+  state: string;
+  winners: number[];
   winner: number | null;
   createdAt: string;
   duration: string;
   randomnessFulfilled: boolean;
+  revealSlot: string;
+  revealSlotHashRecorded: boolean;
+  revealDeadline: string;
+  feesPaid: boolean;
+  claimedTiers: number;
   bump: number;
-  _manualParse?: boolean;
 }
 
-/**
- * FIXED: Parses duration correctly by treating it as u64 instead of i64
- */
-const parseLotteryAccount = (data: Uint8Array, pubkey: PublicKey): ParsedLottery => {
-  if (data.length < 87) {
-    throw new Error(`Invalid lottery account data size: ${data.length}, expected at least 87 bytes`);
-  }
+// Creator share offered by the create form; the platform share comes from its config
+const CREATOR_BPS = 500;
+const BPS_DENOMINATOR = 10000;
 
-  const dv = new DataView(data.buffer, data.byteOffset, data.byteLength);
-  let offset = 8; // Skip 8-byte Anchor discriminator
-  
-  try {
-    console.log('Parsing lottery account:', pubkey.toBase58());
-    console.log('Data length:', data.length);
-
-    // Parse authority (Pubkey - 32 bytes)
-    const authority = new PublicKey(data.slice(offset, offset + 32));
-    console.log('Authority parsed at offset', offset, authority.toBase58());
-    offset += 32;
-
-    // Parse lottery_id (u64 - 8 bytes)
-    const lotteryId = dv.getBigUint64(offset, true);
-    console.log('Lottery ID parsed at offset', offset, lotteryId.toString());
-    offset += 8;
-
-    // Parse ticket_price (u64 - 8 bytes)
-    const ticketPrice = dv.getBigUint64(offset, true);
-    console.log('Ticket price parsed at offset', offset, ticketPrice.toString());
-    offset += 8;
-
-    // Parse max_tickets (u32 - 4 bytes)
-    const maxTickets = dv.getUint32(offset, true);
-    console.log('Max tickets parsed at offset', offset, maxTickets);
-    offset += 4;
-
-    // Parse tickets_sold (u32 - 4 bytes)
-    const ticketsSold = dv.getUint32(offset, true);
-    console.log('Tickets sold parsed at offset', offset, ticketsSold);
-    offset += 4;
-
-    // Parse total_prize_pool (u64 - 8 bytes)
-    const totalPrizePool = dv.getBigUint64(offset, true);
-    console.log('Total prize pool parsed at offset', offset, totalPrizePool.toString());
-    offset += 8;
-
-    // Parse winner (Option<u32>)
-    // Borsh serializes an Option as a 1-byte flag (0 or 1) followed by the inner value only when present.
-    const hasWinner = data[offset] === 1;
-    console.log('Winner flag at offset', offset, hasWinner);
-    offset += 1;
-    let winner: number | null = null;
-    if (hasWinner) {
-      // Only advance and read the inner u32 when the option is Some
-      winner = dv.getUint32(offset, true);
-      console.log('Winner ID parsed at offset', offset, winner);
-      offset += 4;
-    }
+const findPda = (program: anchor.Program, seeds: Buffer[]) =>
+  PublicKey.findProgramAddressSync(seeds, program.programId)[0];
 
-    // Parse created_at (i64 - 8 bytes)
-    // Note: created_at offset depends on whether `winner` contained a u32. We compute it dynamically above.
-    const createdAt = dv.getBigInt64(offset, true);
-    console.log('Created_at parsed at offset', offset, createdAt.toString());
-    offset += 8;
-
-    // Parse duration (u64 - 8 bytes)
-    const durationOffset = offset;
-    const durationUnsigned = dv.getBigUint64(offset, true);
-    console.log('Raw duration bytes at offset', durationOffset, data.slice(durationOffset, durationOffset + 8));
-    console.log('Parsed duration (u64) at offset', durationOffset, durationUnsigned.toString());
-    offset += 8;
-
-    const TEN_YEARS_IN_SECONDS = BigInt(10 * 365 * 24 * 60 * 60);
-    const durationValue = durationUnsigned;
-    if (durationValue > TEN_YEARS_IN_SECONDS) {
-      console.warn(`Suspicious duration value (unsigned): ${durationValue}. Check raw bytes above.`);
-    }
+const findPlatformConfig = (program: anchor.Program) =>
+  findPda(program, [Buffer.from("platform-config")]);
 
-    // Parse randomness_fulfilled (bool - 1 byte)
-    const randomnessFulfilled = data[offset] === 1;
-    console.log('Randomness fulfilled at offset', offset, randomnessFulfilled);
-    offset += 1;
-
-    // Parse bump (u8 - 1 byte)
-    const bump = data[offset];
-    console.log('Bump parsed at offset', offset, bump);
-    offset += 1;
-
-    console.log(`Successfully parsed lottery ID=${lotteryId}, duration=${durationValue}s, tickets=${ticketsSold}/${maxTickets}`);
-
-    return {
-      id: pubkey.toBase58(),
-      authority: authority.toBase58(),
-      lotteryId: lotteryId.toString(),
-      ticketPrice: ticketPrice.toString(),
-      maxTickets,
-      ticketsSold,
-      totalPrizePool: totalPrizePool.toString(),
-      winner,
-      createdAt: createdAt.toString(),
-      duration: durationValue.toString(),
-      randomnessFulfilled,
-      bump,
-      _manualParse: true,
-    };
-  } catch (error) {
-    console.error('Error parsing lottery account:', error);
-    console.error('Account data length:', data.length);
-    console.error('Current offset:', offset);
-    console.error('Raw data slice near duration:', data.slice(offset - 8, offset + 8));
-    throw new Error(`Failed to parse lottery account ${pubkey.toBase58()}: ${error}`);
-  }
-};
+const findTicketRegistry = (program: anchor.Program, lotteryPda: PublicKey) =>
+  findPda(program, [Buffer.from("ticket-registry"), lotteryPda.toBuffer()]);
 
-const parseTicketAccount = (data: Uint8Array, pubkey: PublicKey) => {
-  const dv = new DataView(data.buffer, data.byteOffset, data.byteLength);
-  let offset = 8;
+const findPlayerEntry = (program: anchor.Program, lotteryPda: PublicKey, player: PublicKey) =>
+  findPda(program, [Buffer.from("player-entry"), lotteryPda.toBuffer(), player.toBuffer()]);
 
-  const lottery = new PublicKey(data.slice(offset, offset + 32));
-  offset += 32;
+// The commit-reveal secret never leaves the creator's browser until it is revealed
+const secretStorageKey = (lotteryPda: PublicKey) => `lottery-secret-${lotteryPda.toBase58()}`;
 
-  const player = new PublicKey(data.slice(offset, offset + 32));
-  offset += 32;
+const saveLotterySecret = (lotteryPda: PublicKey, secret: Uint8Array) => {
+  localStorage.setItem(secretStorageKey(lotteryPda), Buffer.from(secret).toString("hex"));
+};
 
-  const ticketNumber = dv.getUint32(offset, true);
-  offset += 4;
+export const hasLotterySecret = (lotteryPda: PublicKey) =>
+  localStorage.getItem(secretStorageKey(lotteryPda)) !== null;
 
-  const purchasedAt = dv.getBigInt64(offset, true);
-  offset += 8;
+const loadLotterySecret = (lotteryPda: PublicKey): number[] | null => {
+  const hex = localStorage.getItem(secretStorageKey(lotteryPda));
+  return hex ? Array.from(Buffer.from(hex, "hex")) : null;
+};
 
-  const bump = data[offset];
+/**
+ * Flattens a lottery decoded through the IDL into the plain values the pages display
+ */
+const parseLotteryAccount = (account: any, pubkey: PublicKey): ParsedLottery => {
+  const winners: number[] = account.winners ?? [];
 
   return {
     id: pubkey.toBase58(),
-    lottery: lottery.toBase58(),
-    player: player.toBase58(),
-    ticketNumber,
-    purchasedAt: purchasedAt.toString(),
-    bump,
-    _manualParse: true,
+    authority: account.authority.toBase58(),
+    lotteryId: account.lotteryId.toString(),
+    ticketPrice: account.ticketPrice.toString(),
+    maxTickets: account.maxTickets,
+    ticketsSold: account.ticketsSold,
+    totalPrizePool: account.totalPrizePool.toString(),
+    state: Object.keys(account.state)[0],
+    winners,
+    winner: winners.length > 0 ? winners[0] : null,
+    createdAt: account.createdAt.toString(),
+    duration: account.duration.toString(),
+    randomnessFulfilled: account.randomnessFulfilled,
+    revealSlot: account.revealSlot.toString(),
+    revealSlotHashRecorded: account.revealSlotHash !== null,
+    revealDeadline: account.revealDeadline.toString(),
+    feesPaid: account.feesPaid,
+    claimedTiers: account.claimedTiers,
+    bump: account.bump,
   };
 };
 
+const parsePlayerEntryAccount = (account: any, pubkey: PublicKey) => ({
+  id: pubkey.toBase58(),
+  lottery: account.lottery.toBase58(),
+  player: account.player.toBase58(),
+  ticketCount: account.ticketCount,
+  ticketNumbers: account.ranges.map((range: any) => ({ start: range.start, count: range.count })),
+  bump: account.bump,
+});
+
 export const getAllLotteries = async (program: anchor.Program) => {
   if (!program) {
     console.warn('Program not available for getAllLotteries');
@@ -340,21 +266,14 @@ export const getAllLotteries = async (program: anchor.Program) => {
   console.log('Fetching all lotteries...');
   
   try {
-    const accounts = await program.provider.connection.getProgramAccounts(
-      program.programId, 
-      {
-        filters: [{ dataSize: LOTTERY_ACCOUNT_SIZE }],
-        commitment: 'confirmed'
-      }
-    );
+    const lotteries = await (program.account as any).lottery.all([], 'confirmed');
+    console.log(`Found ${lotteries.length} lottery accounts`);
     
-    console.log(`Found ${accounts.length} lottery accounts via manual fetch`);
-    
-    const parsedLotteries = accounts.map(acc => {
+    const parsedLotteries = lotteries.map((l: any) => {
       try {
-        return parseLotteryAccount(acc.account.data, acc.pubkey);
+        return parseLotteryAccount(l.account, l.publicKey);
       } catch (parseError) {
-        console.error('Error parsing lottery account:', acc.pubkey.toString(), parseError);
+        console.error('Error parsing lottery account:', l.publicKey.toString(), parseError);
         return null;
       }
     }).filter(Boolean);
@@ -364,20 +283,7 @@ export const getAllLotteries = async (program: anchor.Program) => {
     
   } catch (error) {
     console.error('Failed to fetch lotteries:', error);
-    
-    try {
-      console.log('Trying Anchor fetch as fallback...');
-      const lotteries = await (program.account as any).lottery.all([], 'confirmed');
-      console.log(`Found ${lotteries.length} lotteries via Anchor fallback`);
-      
-      return lotteries.map((l: any) => ({
-        id: l.publicKey.toBase58(),
-        ...l.account,
-      }));
-    } catch (anchorError) {
-      console.error('Both manual and Anchor fetch failed:', anchorError);
-      return [];
-    }
+    return [];
   }
 };
 
@@ -390,24 +296,18 @@ export const getCreatedLotteries = async (program: anchor.Program, wallet: Ancho
   console.log(`Fetching lotteries created by: ${wallet.publicKey.toBase58()}`);
   
   try {
-    const accounts = await program.provider.connection.getProgramAccounts(
-      program.programId, 
-      {
-        filters: [
-          { dataSize: LOTTERY_ACCOUNT_SIZE },
-          { memcmp: { offset: 8, bytes: wallet.publicKey.toBase58() } },
-        ],
-        commitment: 'confirmed'
-      }
-    );
+    // The authority is the first field after the discriminator
+    const lotteries = await (program.account as any).lottery.all([
+      { memcmp: { offset: 8, bytes: wallet.publicKey.toBase58() } },
+    ], 'confirmed');
     
-    console.log(`Found ${accounts.length} created lottery accounts via manual fetch`);
+    console.log(`Found ${lotteries.length} created lottery accounts`);
     
-    const parsedLotteries = accounts.map(acc => {
+    const parsedLotteries = lotteries.map((l: any) => {
       try {
-        return parseLotteryAccount(acc.account.data, acc.pubkey);
+        return parseLotteryAccount(l.account, l.publicKey);
       } catch (parseError) {
-        console.error('Error parsing created lottery account:', acc.pubkey.toString(), parseError);
+        console.error('Error parsing created lottery account:', l.publicKey.toString(), parseError);
         return null;
       }
     }).filter(Boolean);
@@ -417,28 +317,13 @@ export const getCreatedLotteries = async (program: anchor.Program, wallet: Ancho
     
   } catch (error) {
     console.error('Failed to fetch created lotteries:', error);
-    
-    try {
-      console.log('Trying Anchor fetch for created lotteries as fallback...');
-      const lotteries = await (program.account as any).lottery.all([
-        {
-          memcmp: { offset: 8, bytes: wallet.publicKey.toBase58() },
-        },
-      ], 'confirmed');
-      
-      console.log(`Found ${lotteries.length} created lotteries via Anchor fallback`);
-      
-      return lotteries.map((l: any) => ({
-        id: l.publicKey.toBase58(),
-        ...l.account,
-      }));
-    } catch (anchorError) {
-      console.error('Both manual and Anchor fetch failed for created lotteries:', anchorError);
-      return [];
-    }
+    return [];
   }
 };
 
+/**
+ * Fetches the player's entries, each holding every ticket they have in one lottery
+ */
 export const getMyTickets = async (program: anchor.Program, wallet: AnchorWallet) => {
   if (!program || !wallet) {
     console.warn('Program or wallet not available for getMyTickets');
@@ -448,73 +333,43 @@ export const getMyTickets = async (program: anchor.Program, wallet: AnchorWallet
   console.log(`Fetching tickets for: ${wallet.publicKey.toBase58()}`);
   
   try {
-    const accounts = await program.provider.connection.getProgramAccounts(
-      program.programId, 
-      {
-        filters: [
-          { dataSize: TICKET_ACCOUNT_SIZE },
-          { memcmp: { offset: 40, bytes: wallet.publicKey.toBase58() } },
-        ],
-        commitment: 'confirmed'
-      }
-    );
+    // The player follows the lottery in each entry
+    const entries = await (program.account as any).playerEntry.all([
+      { memcmp: { offset: 40, bytes: wallet.publicKey.toBase58() } },
+    ], 'confirmed');
     
-    console.log(`Found ${accounts.length} ticket accounts via manual fetch`);
+    console.log(`Found ${entries.length} player entries`);
     
-    const parsedTickets = accounts.map(acc => {
+    const parsedTickets = entries.map((e: any) => {
       try {
-        return parseTicketAccount(acc.account.data, acc.pubkey);
+        return parsePlayerEntryAccount(e.account, e.publicKey);
       } catch (parseError) {
-        console.error('Error parsing ticket account:', acc.pubkey.toString(), parseError);
+        console.error('Error parsing player entry:', e.publicKey.toString(), parseError);
         return null;
       }
     }).filter(Boolean);
     
-    console.log(`Successfully parsed ${parsedTickets.length} tickets`);
+    console.log(`Successfully parsed ${parsedTickets.length} entries`);
     return parsedTickets;
     
   } catch (error) {
     console.error('Failed to fetch tickets:', error);
-    
-    try {
-      console.log('Trying Anchor fetch for tickets as fallback...');
-      const tickets = await (program.account as any).ticket.all([
-        {
-          memcmp: { offset: 40, bytes: wallet.publicKey.toBase58() },
-        },
-      ], 'confirmed');
-      
-      console.log(`Found ${tickets.length} tickets via Anchor fallback`);
-      
-      return tickets.map((t: any) => ({
-        id: t.publicKey.toBase58(),
-        ...t.account,
-      }));
-    } catch (anchorError) {
-      console.error('Both manual and Anchor fetch failed for tickets:', anchorError);
-      return [];
-    }
+    return [];
   }
 };
 
 export const getLotteryByPda = async (program: anchor.Program, lotteryPda: PublicKey) => {
   try {
-    const info = await program.provider.connection.getAccountInfo(lotteryPda, 'confirmed');
-    if (!info) {
+    const account = await (program.account as any).lottery.fetchNullable(lotteryPda, 'confirmed');
+    if (!account) {
       console.log(`Lottery account not found: ${lotteryPda.toString()}`);
       return null;
     }
     
-    return parseLotteryAccount(info.data, lotteryPda);
+    return parseLotteryAccount(account, lotteryPda);
   } catch (error) {
-    console.warn(`Failed to fetch lottery ${lotteryPda.toString()} via manual parsing:`, error);
-    
-    try {
-      return await (program.account as any).lottery.fetch(lotteryPda);
-    } catch (anchorError) {
-      console.error(`Both manual and Anchor fetch failed for lottery ${lotteryPda.toString()}:`, anchorError);
-      return null;
-    }
+    console.error(`Failed to fetch lottery ${lotteryPda.toString()}:`, error);
+    return null;
   }
 };
 
@@ -567,16 +422,36 @@ export const createLottery = async (
 
     console.log(`Creating lottery with duration: ${duration} seconds`);
 
+    // The platform takes its default share, a single winner takes the rest
+    const platformConfig = findPlatformConfig(program);
+    const { defaultFeeBps } = await (program.account as any).platformConfig.fetch(platformConfig);
+    const prizeSplit = {
+      winnerBps: BPS_DENOMINATOR - CREATOR_BPS - defaultFeeBps,
+      creatorBps: CREATOR_BPS,
+      platformBps: defaultFeeBps,
+      tierBps: [BPS_DENOMINATOR],
+    };
+
+    // Commit to a secret revealed once sales close, so the winner cannot be chosen
+    const secret = crypto.getRandomValues(new Uint8Array(32));
+    const commitment = Array.from(new Uint8Array(await crypto.subtle.digest("SHA-256", secret)));
+    saveLotterySecret(lotteryPda, secret);
+
     const tx = await program.methods
       .initializeLottery(
         new anchor.BN(lotteryId), 
         ticketPriceLamports, 
-        new anchor.BN(maxTickets), 
-        new anchor.BN(duration) // Duration is sent correctly as seconds
+        maxTickets,
+        new anchor.BN(duration), // Duration is sent correctly as seconds
+        prizeSplit,
+        { commitReveal: { commitment } },
+        false
       )
       .accounts({
         lottery: lotteryPda,
         authority: wallet.publicKey,
+        ticketRegistry: findTicketRegistry(program, lotteryPda),
+        platformConfig,
         systemProgram: SystemProgram.programId,
       })
       .transaction();
@@ -632,6 +507,7 @@ export const buyTicket = async (
   cleanupOldTransactions();
 
   const lotteryPda = new PublicKey(lotteryPdaString);
+  const playerEntryPda = findPlayerEntry(program, lotteryPda, wallet.publicKey);
   
   const transactionId = `buy-ticket-${lotteryPdaString}-${wallet.publicKey.toString()}-${Math.floor(Date.now() / 1000)}`;
   const now = Date.now();
@@ -645,21 +521,14 @@ export const buyTicket = async (
   
   recentTransactions.set(transactionId, now);
 
-  // ticketPda may be populated during the attempt; declare it here so the catch handler can inspect it
-  let ticketPda: PublicKey | null = null;
+  // Tickets held before this purchase, so the catch handler can tell whether it went through
+  let previousTicketCount: number | null = null;
 
   try {
-    let lotteryAccount: any;
-
-    try {
-      lotteryAccount = await (program.account as any).lottery.fetch(lotteryPda);
-    } catch {
-      const info = await program.provider.connection.getAccountInfo(lotteryPda);
-      if (!info) throw new Error("Lottery account not found");
-      lotteryAccount = parseLotteryAccount(info.data, lotteryPda);
-    }
+    const lotteryAccount = await getLotteryByPda(program, lotteryPda);
+    if (!lotteryAccount) throw new Error("Lottery account not found");
 
-    const lotteryId = parseInt(lotteryAccount.lotteryId);
+    const lotteryId = lotteryAccount.lotteryId;
     const ticketPriceLamports = BigInt(lotteryAccount.ticketPrice);
     const balance = BigInt(await program.provider.connection.getBalance(wallet.publicKey));
     const requiredBalance = ticketPriceLamports + BigInt(0.01 * LAMPORTS_PER_SOL);
@@ -674,34 +543,16 @@ export const buyTicket = async (
       throw new Error("This lottery is sold out");
     }
 
-    const ticketPdaResult = PublicKey.findProgramAddressSync(
-      [
-        Buffer.from("ticket"),
-        lotteryPda.toBuffer(),
-        new anchor.BN(lotteryAccount.ticketsSold).toArrayLike(Buffer, "le", 4),
-      ],
-      program.programId
-    );
-    ticketPda = ticketPdaResult[0];
-
-    try {
-      const existingTicket = await program.provider.connection.getAccountInfo(ticketPda);
-      if (existingTicket) {
-        throw new Error("Ticket PDA already exists - this may be a duplicate transaction");
-      }
-    } catch (error: any) {
-      if (!error.message.includes('already exists')) {
-        console.warn('Could not check existing ticket:', error.message);
-      } else {
-        throw error;
-      }
-    }
+    const entry = await (program.account as any).playerEntry.fetchNullable(playerEntryPda);
+    previousTicketCount = entry ? entry.ticketCount : 0;
 
     const tx = await program.methods.buyTicket(new anchor.BN(lotteryId))
       .accounts({
         player: wallet.publicKey,
         lottery: lotteryPda,
-        ticket: ticketPda,
+        platformConfig: findPlatformConfig(program),
+        playerEntry: playerEntryPda,
+        ticketRegistry: findTicketRegistry(program, lotteryPda),
         systemProgram: SystemProgram.programId,
       })
       .transaction();
@@ -722,26 +573,26 @@ export const buyTicket = async (
   } catch (error: any) {
     recentTransactions.delete(transactionId);
 
-    // If this looks like a duplicate/already-processed error, verify whether the ticket PDA
-    // was actually created. If the PDA exists, try to return a signature so the UI treats it as success.
+    // If this looks like a duplicate/already-processed error, verify whether the player's entry
+    // gained a ticket. If it did, try to return a signature so the UI treats it as success.
     if (error && typeof error.message === 'string' && (error.message.includes('already been processed') || error.message.includes('duplicate') || error.message.includes('already processed'))) {
       try {
-        if (ticketPda) {
-          const ticketInfo = await program.provider.connection.getAccountInfo(ticketPda);
-          if (ticketInfo) {
-            const sig = await findRecentSignatureForAddress(program.provider.connection, ticketPda);
+        if (previousTicketCount !== null) {
+          const entry = await (program.account as any).playerEntry.fetchNullable(playerEntryPda);
+          if (entry && entry.ticketCount > previousTicketCount) {
+            const sig = await findRecentSignatureForAddress(program.provider.connection, playerEntryPda);
             if (sig) {
-              console.log('Detected ticket PDA after duplicate error, returning signature:', sig);
+              console.log('Detected new ticket after duplicate error, returning signature:', sig);
               return sig;
             }
-            console.log('Ticket PDA exists after duplicate error but no recent signature found, returning placeholder success');
+            console.log('Entry gained a ticket after duplicate error but no recent signature found, returning placeholder success');
             return 'transaction_already_processed';
           }
         } else {
-          console.warn('ticketPda was not set before error; cannot verify duplicate transaction via PDA');
+          console.warn('Entry was not read before error; cannot verify duplicate transaction');
         }
       } catch (verifyErr) {
-        console.warn('Failed to verify ticket PDA after duplicate error:', verifyErr);
+        console.warn('Failed to verify player entry after duplicate error:', verifyErr);
       }
     }
 
@@ -749,22 +600,89 @@ export const buyTicket = async (
   }
 };
 
-export const fulfillRandomness = async (
+/**
+ * Closes ticket sales once the lottery has expired or sold out; anyone may call it
+ */
+export const closeSales = async (
   program: anchor.Program,
   lotteryPda: PublicKey,
-  wallet: AnchorWallet,
-  randomness: number[]
+  wallet: AnchorWallet
 ) => {
   if (!program || !wallet) {
     throw new Error("Program or wallet not available");
   }
 
-  const randomnessArray = randomness.slice(0, 32).concat(Array(32 - randomness.length).fill(0));
+  const tx = await program.methods.closeSales()
+    .accounts({
+      caller: wallet.publicKey,
+      lottery: lotteryPda,
+    })
+    .transaction();
 
-  const tx = await program.methods.fulfillRandomness(randomnessArray)
+  const signature = await sendTransactionWithRetry(
+    program.provider.connection,
+    tx,
+    wallet,
+    `Close Sales for Lottery ${lotteryPda.toString()}`
+  );
+
+  return signature;
+};
+
+/**
+ * Records the hash of the lottery's reveal slot before the SlotHashes sysvar drops it,
+ * so the secret can still be revealed later; anyone may call it
+ */
+export const recordSlotHash = async (
+  program: anchor.Program,
+  lotteryPda: PublicKey,
+  wallet: AnchorWallet
+) => {
+  if (!program || !wallet) {
+    throw new Error("Program or wallet not available");
+  }
+
+  const tx = await program.methods.recordSlotHash()
+    .accounts({
+      caller: wallet.publicKey,
+      lottery: lotteryPda,
+      slotHashes: SYSVAR_SLOT_HASHES_PUBKEY,
+    })
+    .transaction();
+
+  const signature = await sendTransactionWithRetry(
+    program.provider.connection,
+    tx,
+    wallet,
+    `Record Slot Hash for Lottery ${lotteryPda.toString()}`
+  );
+
+  return signature;
+};
+
+/**
+ * Reveals the secret committed when this browser created the lottery, drawing its winners.
+ * Only succeeds after the lottery's reveal slot and before its reveal deadline.
+ */
+export const revealRandomness = async (
+  program: anchor.Program,
+  lotteryPda: PublicKey,
+  wallet: AnchorWallet
+) => {
+  if (!program || !wallet) {
+    throw new Error("Program or wallet not available");
+  }
+
+  const secret = loadLotterySecret(lotteryPda);
+  if (!secret) {
+    throw new Error("The secret of this lottery is not stored in this browser");
+  }
+
+  const tx = await program.methods.revealRandomness(secret)
     .accounts({
-      authority: wallet.publicKey,
+      caller: wallet.publicKey,
       lottery: lotteryPda,
+      slotHashes: SYSVAR_SLOT_HASHES_PUBKEY,
     })
     .transaction();
 
@@ -772,18 +690,20 @@ export const fulfillRandomness = async (
     program.provider.connection,
     tx,
     wallet,
-    `Fulfill Randomness for Lottery ${lotteryPda.toString()}`
+    `Reveal Randomness for Lottery ${lotteryPda.toString()}`
   );
 
   return signature;
 };
 
-export const payoutWinner = async (
+/**
+ * Settles a drawn lottery: pays the creator and platform shares, and the crank reward to the keepers
+ * who closed sales and requested the draw, with the rest to the caller
+ */
+export const payout = async (
   program: anchor.Program,
   lotteryPda: PublicKey,
   wallet: AnchorWallet,
-  winnerTicketPda: PublicKey,
-  winner: PublicKey,
   lotteryCreator: PublicKey,
   platformFeeAccount: PublicKey
 ) => {
@@ -791,15 +711,73 @@ export const payoutWinner = async (
     throw new Error("Program or wallet not available");
   }
 
+  const { salesCloser, drawRequester } = await (program.account as any).lottery.fetch(lotteryPda);
+
   const tx = await program.methods.payout()
     .accounts({
-      authority: wallet.publicKey,
+      caller: wallet.publicKey,
       lottery: lotteryPda,
-      winnerTicket: winnerTicketPda,
-      winner,
       lotteryCreator,
+      platformConfig: findPlatformConfig(program),
       platformFeeAccount,
-      systemProgram: SystemProgram.programId,
+      salesCloser,
+      drawRequester,
+    })
+    .transaction();
+
+  const signature = await sendTransactionWithRetry(
+    program.provider.connection,
+    tx,
+    wallet,
+    `Payout for Lottery ${lotteryPda.toString()}`
+  );
+
+  return signature;
+};
+
+/**
+ * Pays the prize of `tier` to the owner of its winning ticket, resolved through the ticket registry
+ */
+export const claimPrize = async (
+  program: anchor.Program,
+  lotteryPda: PublicKey,
+  wallet: AnchorWallet,
+  tier: number
+) => {
+  if (!program || !wallet) {
+    throw new Error("Program or wallet not available");
+  }
+
+  const lottery = await (program.account as any).lottery.fetch(lotteryPda);
+  const ticket: number | undefined = lottery.winners[tier];
+  if (ticket === undefined) {
+    throw new Error(`Lottery has no winner for tier ${tier}`);
+  }
+
+  // The registry maps each ticket number to its owner's entry index
+  const ticketRegistry = findTicketRegistry(program, lotteryPda);
+  const registryInfo = await program.provider.connection.getAccountInfo(ticketRegistry);
+  if (!registryInfo) {
+    throw new Error("Ticket registry not found");
+  }
+  const registryHeaderSize = 8 + 32 + 4 + 4;
+  const ownerIndex = registryInfo.data.readUInt32LE(registryHeaderSize + 4 * ticket);
+
+  const entries = await (program.account as any).playerEntry.all([
+    { memcmp: { offset: 8, bytes: lotteryPda.toBase58() } },
+  ]);
+  const winnerEntry = entries.find((e: any) => e.account.index === ownerIndex);
+  if (!winnerEntry) {
+    throw new Error(`Entry holding winning ticket #${ticket} not found`);
+  }
+
+  const tx = await program.methods.claimPrize(tier)
+    .accounts({
+      claimer: wallet.publicKey,
+      winner: winnerEntry.account.player,
+      lottery: lotteryPda,
+      ticketRegistry,
+      winnerEntry: winnerEntry.publicKey,
     })
     .transaction();
 
@@ -807,7 +785,7 @@ export const payoutWinner = async (
     program.provider.connection,
     tx,
     wallet,
-    `Payout Winner for Lottery ${lotteryPda.toString()}`
+    `Claim Tier ${tier} Prize for Lottery ${lotteryPda.toString()}`
   );
 
   return signature;
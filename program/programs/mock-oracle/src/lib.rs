@@ -0,0 +1,45 @@
+#![allow(deprecated)]
+
+use anchor_lang::prelude::*;
+use lottery::program::Lottery as LotteryProgram;
+use lottery::state::Lottery;
+use lottery::ORACLE_AUTHORITY_SEED;
+
+declare_id!("2teZCQaK4n5yuedVgqd7PyPQfzYFMZK8D7FhYwtFKHiw");
+
+/// Minimal oracle for tests: forwards whatever randomness it is given to the
+/// lottery's `fulfill_randomness` callback, signed by its authority PDA.
+#[program]
+pub mod mock_oracle {
+    use super::*;
+
+    pub fn fulfill(ctx: Context<Fulfill>, randomness: [u8; 32]) -> Result<()> {
+        let signer_seeds: &[&[&[u8]]] = &[&[ORACLE_AUTHORITY_SEED, &[ctx.bumps.oracle_authority]]];
+
+        let cpi_ctx = CpiContext::new_with_signer(
+            ctx.accounts.lottery_program.to_account_info(),
+            lottery::cpi::accounts::FulfillRandomness {
+                oracle_authority: ctx.accounts.oracle_authority.to_account_info(),
+                lottery: ctx.accounts.lottery.to_account_info(),
+            },
+            signer_seeds,
+        );
+
+        lottery::cpi::fulfill_randomness(cpi_ctx, randomness)
+    }
+}
+
+#[derive(Accounts)]
+pub struct Fulfill<'info> {
+    /// CHECK: Only used as the CPI signer
+    #[account(
+        seeds = [ORACLE_AUTHORITY_SEED],
+        bump
+    )]
+    pub oracle_authority: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub lottery: Account<'info, Lottery>,
+
+    pub lottery_program: Program<'info, LotteryProgram>,
+}
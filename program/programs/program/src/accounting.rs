@@ -0,0 +1,361 @@
+//! Accounting for the funds a lottery holds.
+//!
+//! A SOL lottery keeps its pool in its own lamports. Its balance is its
+//! rent-exempt minimum plus whatever it owes, so only lamports above that
+//! minimum may ever leave it. Every direct lamport move goes through
+//! [`transfer_lamports`], which checks this before touching either balance.
+//!
+//! A token lottery keeps its pool in a vault token account owned by the
+//! lottery PDA, seeded by `[b"vault", lottery]`. Both the Token and
+//! Token-2022 programs are supported. With a transfer-fee mint the vault
+//! receives less than the player sends, so purchases record the amount that
+//! actually arrived and payouts split the vault balance.
+//!
+//! A series' jackpot holds its funds the same way, in its own lamports or in
+//! a vault seeded by `[b"vault", jackpot]`.
+
+use anchor_lang::prelude::*;
+use anchor_spl::associated_token::get_associated_token_address_with_program_id;
+use anchor_spl::token_2022::spl_token_2022::extension::{
+    BaseStateWithExtensions, ExtensionType, StateWithExtensions,
+};
+use anchor_spl::token_2022::spl_token_2022::state::Mint as MintState;
+use anchor_spl::token_2022::Token2022;
+use anchor_spl::token_interface::{self, Mint, TokenAccount, TokenInterface};
+use crate::errors::LotteryError;
+use crate::state::{Jackpot, Lottery};
+
+/// Lamports held by `account` above its rent-exempt minimum.
+pub fn spendable_lamports(account: &AccountInfo) -> Result<u64> {
+    let rent_minimum = Rent::get()?.minimum_balance(account.data_len());
+    account
+        .lamports()
+        .checked_sub(rent_minimum)
+        .ok_or(LotteryError::BelowRentExemption.into())
+}
+
+/// Fails unless `account` holds at least `owed` lamports above its
+/// rent-exempt minimum.
+pub fn ensure_covers(account: &AccountInfo, owed: u64) -> Result<()> {
+    require!(
+        spendable_lamports(account)? >= owed,
+        LotteryError::InsufficientPrizePool
+    );
+    Ok(())
+}
+
+/// Moves `amount` lamports from the program-owned `from` to `to`, leaving
+/// `from` rent-exempt.
+pub fn transfer_lamports(from: &AccountInfo, to: &AccountInfo, amount: u64) -> Result<()> {
+    ensure_covers(from, amount)?;
+
+    let from_balance = from
+        .lamports()
+        .checked_sub(amount)
+        .ok_or(LotteryError::MathOverflow)?;
+    let to_balance = to
+        .lamports()
+        .checked_add(amount)
+        .ok_or(LotteryError::MathOverflow)?;
+
+    **from.try_borrow_mut_lamports()? = from_balance;
+    **to.try_borrow_mut_lamports()? = to_balance;
+
+    Ok(())
+}
+
+/// Token accounts a token lottery moves its funds through.
+pub struct Treasury<'a, 'info> {
+    pub vault: &'a InterfaceAccount<'info, TokenAccount>,
+    pub mint: &'a InterfaceAccount<'info, Mint>,
+    pub token_program: &'a Interface<'info, TokenInterface>,
+}
+
+impl<'a, 'info> Treasury<'a, 'info> {
+    /// The lottery's treasury, or `None` for a SOL lottery. Token lotteries
+    /// must pass their mint, vault and token program.
+    pub fn of(
+        lottery: &Lottery,
+        vault: Option<&'a InterfaceAccount<'info, TokenAccount>>,
+        mint: Option<&'a InterfaceAccount<'info, Mint>>,
+        token_program: Option<&'a Interface<'info, TokenInterface>>,
+    ) -> Result<Option<Self>> {
+        Self::for_mint(lottery.mint, vault, mint, token_program)
+    }
+
+    /// The treasury holding `expected_mint` tokens in `vault`, or `None`
+    /// when funds are held in SOL.
+    pub fn for_mint(
+        expected_mint: Option<Pubkey>,
+        vault: Option<&'a InterfaceAccount<'info, TokenAccount>>,
+        mint: Option<&'a InterfaceAccount<'info, Mint>>,
+        token_program: Option<&'a Interface<'info, TokenInterface>>,
+    ) -> Result<Option<Self>> {
+        let Some(expected_mint) = expected_mint else {
+            return Ok(None);
+        };
+        let (Some(vault), Some(mint), Some(token_program)) = (vault, mint, token_program) else {
+            return err!(LotteryError::MissingTokenAccounts);
+        };
+        require_keys_eq!(mint.key(), expected_mint, LotteryError::InvalidMint);
+
+        Ok(Some(Self {
+            vault,
+            mint,
+            token_program,
+        }))
+    }
+}
+
+/// Extensions enabled on `mint`; always empty for legacy Token mints.
+pub fn mint_extensions(mint: &AccountInfo) -> Result<Vec<ExtensionType>> {
+    if *mint.owner != Token2022::id() {
+        return Ok(Vec::new());
+    }
+    let data = mint.try_borrow_data()?;
+    let mint_state = StateWithExtensions::<MintState>::unpack(&data)?;
+    Ok(mint_state.get_extension_types()?)
+}
+
+/// Rejects mints whose extensions would stop the vault from paying out:
+/// non-transferable tokens cannot leave it, a permanent delegate could drain
+/// it, and transfer hooks need accounts settlement does not pass.
+pub fn validate_mint(mint: &AccountInfo) -> Result<()> {
+    let unsupported = mint_extensions(mint)?.into_iter().any(|extension| {
+        matches!(
+            extension,
+            ExtensionType::NonTransferable
+                | ExtensionType::PermanentDelegate
+                | ExtensionType::TransferHook
+        )
+    });
+    require!(!unsupported, LotteryError::UnsupportedMintExtension);
+    Ok(())
+}
+
+/// Current balance of `vault`, read from its data rather than from a copy
+/// deserialized before a transfer.
+pub fn vault_balance(vault: &AccountInfo) -> Result<u64> {
+    let data = vault.try_borrow_data()?;
+    Ok(TokenAccount::try_deserialize(&mut &data[..])?.amount)
+}
+
+/// Fails unless the lottery's funds cover `owed`: lamports above rent for a
+/// SOL lottery, or the vault balance for a token lottery.
+pub fn ensure_pool_covered(
+    lottery: &Account<Lottery>,
+    treasury: Option<&Treasury>,
+    owed: u64,
+) -> Result<()> {
+    match treasury {
+        None => ensure_covers(&lottery.to_account_info(), owed),
+        Some(treasury) => {
+            require!(
+                vault_balance(&treasury.vault.to_account_info())? >= owed,
+                LotteryError::InsufficientPrizePool
+            );
+            Ok(())
+        }
+    }
+}
+
+/// Pool to split at settlement: the recorded pool for a SOL lottery, or the
+/// whole vault balance for a token lottery.
+pub fn settlement_pool(lottery: &Lottery, treasury: Option<&Treasury>) -> Result<u64> {
+    match treasury {
+        None => Ok(lottery.total_prize_pool),
+        Some(treasury) => vault_balance(&treasury.vault.to_account_info()),
+    }
+}
+
+/// Account that receives `owner`'s funds: `owner` itself for a SOL lottery,
+/// or its associated token account for a token lottery.
+pub fn payee<'info>(
+    treasury: Option<&Treasury<'_, 'info>>,
+    owner: &AccountInfo<'info>,
+    token_account: Option<&InterfaceAccount<'info, TokenAccount>>,
+) -> Result<AccountInfo<'info>> {
+    match treasury {
+        None => Ok(owner.clone()),
+        Some(treasury) => {
+            let token_account = token_account.ok_or(LotteryError::MissingTokenAccounts)?;
+            require_keys_eq!(
+                token_account.key(),
+                get_associated_token_address_with_program_id(
+                    owner.key,
+                    &treasury.mint.key(),
+                    &treasury.token_program.key(),
+                ),
+                LotteryError::InvalidTokenAccount
+            );
+            Ok(token_account.to_account_info())
+        }
+    }
+}
+
+/// Pays `amount` out of the lottery's funds to `to`, as returned by [`payee`].
+pub fn pay_from_lottery<'info>(
+    lottery: &Account<'info, Lottery>,
+    treasury: Option<&Treasury<'_, 'info>>,
+    to: &AccountInfo<'info>,
+    amount: u64,
+) -> Result<()> {
+    let Some(treasury) = treasury else {
+        return transfer_lamports(&lottery.to_account_info(), to, amount);
+    };
+
+    lottery.with_signer_seeds(|signer_seeds| {
+        let cpi_ctx = CpiContext::new_with_signer(
+            treasury.token_program.to_account_info(),
+            token_interface::TransferChecked {
+                from: treasury.vault.to_account_info(),
+                mint: treasury.mint.to_account_info(),
+                to: to.clone(),
+                authority: lottery.to_account_info(),
+            },
+            signer_seeds,
+        );
+        token_interface::transfer_checked(cpi_ctx, amount, treasury.mint.decimals)
+    })
+}
+
+/// Pays `amount` out of a series' jackpot to `to`. The jackpot's
+/// `treasury`, if any, holds the jackpot vault.
+pub fn pay_from_jackpot<'info>(
+    jackpot: &Account<'info, Jackpot>,
+    treasury: Option<&Treasury<'_, 'info>>,
+    to: &AccountInfo<'info>,
+    amount: u64,
+) -> Result<()> {
+    let Some(treasury) = treasury else {
+        return transfer_lamports(&jackpot.to_account_info(), to, amount);
+    };
+
+    let signer_seeds: &[&[&[u8]]] = &[&[b"jackpot", jackpot.series.as_ref(), &[jackpot.bump]]];
+    let cpi_ctx = CpiContext::new_with_signer(
+        treasury.token_program.to_account_info(),
+        token_interface::TransferChecked {
+            from: treasury.vault.to_account_info(),
+            mint: treasury.mint.to_account_info(),
+            to: to.clone(),
+            authority: jackpot.to_account_info(),
+        },
+        signer_seeds,
+    );
+    token_interface::transfer_checked(cpi_ctx, amount, treasury.mint.decimals)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::solana_program::entrypoint::SUCCESS;
+    use anchor_lang::solana_program::program_stubs::{set_syscall_stubs, SyscallStubs};
+
+    const DATA_LEN: usize = 100;
+
+    /// Lets `Rent::get` succeed off-chain. It starts from the default rent,
+    /// so there is nothing to write.
+    struct DefaultRent;
+
+    impl SyscallStubs for DefaultRent {
+        fn sol_get_rent_sysvar(&self, _var_addr: *mut u8) -> u64 {
+            SUCCESS
+        }
+    }
+
+    fn rent_minimum() -> u64 {
+        Rent::default().minimum_balance(DATA_LEN)
+    }
+
+    /// Runs `test` with a program-owned account of `DATA_LEN` bytes holding
+    /// `lamports` and an empty recipient, then returns both balances.
+    fn with_accounts(lamports: u64, test: impl FnOnce(&AccountInfo, &AccountInfo)) -> (u64, u64) {
+        set_syscall_stubs(Box::new(DefaultRent));
+
+        let (from_key, to_key) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let system = Pubkey::default();
+        let (mut from_lamports, mut to_lamports) = (lamports, 0);
+        let (mut from_data, mut to_data) = (vec![0; DATA_LEN], Vec::new());
+        let from = AccountInfo::new(
+            &from_key,
+            false,
+            true,
+            &mut from_lamports,
+            &mut from_data,
+            &crate::ID,
+            false,
+            0,
+        );
+        let to = AccountInfo::new(
+            &to_key,
+            false,
+            true,
+            &mut to_lamports,
+            &mut to_data,
+            &system,
+            false,
+            0,
+        );
+        test(&from, &to);
+
+        (from.lamports(), to.lamports())
+    }
+
+    #[test]
+    fn spendable_lamports_exclude_rent_minimum() {
+        with_accounts(rent_minimum() + 500, |account, _| {
+            assert_eq!(spendable_lamports(account).unwrap(), 500);
+        });
+    }
+
+    #[test]
+    fn spendable_lamports_reject_account_below_rent_exemption() {
+        with_accounts(rent_minimum() - 1, |account, _| {
+            assert_eq!(
+                spendable_lamports(account).unwrap_err(),
+                LotteryError::BelowRentExemption.into()
+            );
+        });
+    }
+
+    #[test]
+    fn ensure_covers_rejects_more_than_spendable() {
+        with_accounts(rent_minimum() + 500, |account, _| {
+            assert!(ensure_covers(account, 500).is_ok());
+            assert_eq!(
+                ensure_covers(account, 501).unwrap_err(),
+                LotteryError::InsufficientPrizePool.into()
+            );
+        });
+    }
+
+    #[test]
+    fn transfer_lamports_leaves_rent_minimum() {
+        let balances = with_accounts(rent_minimum() + 500, |from, to| {
+            transfer_lamports(from, to, 500).unwrap();
+        });
+        assert_eq!(balances, (rent_minimum(), 500));
+    }
+
+    #[test]
+    fn transfer_lamports_cannot_drain_below_rent_exemption() {
+        let balances = with_accounts(rent_minimum() + 500, |from, to| {
+            assert_eq!(
+                transfer_lamports(from, to, 501).unwrap_err(),
+                LotteryError::InsufficientPrizePool.into()
+            );
+        });
+        assert_eq!(balances, (rent_minimum() + 500, 0));
+    }
+
+    #[test]
+    fn transfer_lamports_rejects_account_already_below_rent_exemption() {
+        let balances = with_accounts(rent_minimum() - 1, |from, to| {
+            assert_eq!(
+                transfer_lamports(from, to, 1).unwrap_err(),
+                LotteryError::BelowRentExemption.into()
+            );
+        });
+        assert_eq!(balances, (rent_minimum() - 1, 0));
+    }
+}
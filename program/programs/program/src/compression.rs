@@ -0,0 +1,146 @@
+//! Compressed tickets, kept as leaves of an spl-account-compression tree.
+//!
+//! A lottery with a `ticket_tree` stores no `PlayerEntry` or registry slot
+//! per ticket. Each purchase appends one leaf per ticket instead:
+//!
+//! `keccak(lottery || player || ticket_number as u32 LE || purchased_at as i64 LE)`
+//!
+//! Leaves are appended in ticket order, so a ticket's leaf index is its
+//! ticket number. The `CompressedTicketsBought` event carries everything
+//! needed to rebuild the leaves off-chain. A winner claims by presenting the
+//! leaf's fields with a proof, checked by the compression program's
+//! `verify_leaf`. A refunded ticket's leaf is replaced by the empty leaf so it
+//! cannot be refunded twice.
+//!
+//! The compression program is reached by building its instructions by hand
+//! from their Anchor discriminators, and the lottery PDA is the tree's
+//! authority.
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::solana_program::keccak::hashv;
+use anchor_lang::solana_program::program::{invoke, invoke_signed};
+use crate::state::Lottery;
+
+/// The spl-account-compression program
+pub const ACCOUNT_COMPRESSION_ID: Pubkey = pubkey!("cmtDvXumGCrqC1Age74AVPhSRVXJMd8PJS91L8KbNCK");
+/// The noop program the compression program logs tree changes through
+pub const NOOP_ID: Pubkey = pubkey!("noopb9bkMVfRPU8AsbpTUg8AQkHtKwMYZiFUjNRtMmV");
+/// Leaf left in place of a refunded ticket
+pub const EMPTY_LEAF: [u8; 32] = [0; 32];
+
+const INIT_EMPTY_MERKLE_TREE: [u8; 8] = [191, 11, 119, 7, 180, 107, 220, 110];
+const APPEND: [u8; 8] = [149, 120, 18, 222, 236, 225, 88, 203];
+const VERIFY_LEAF: [u8; 8] = [124, 220, 22, 223, 104, 10, 250, 224];
+const REPLACE_LEAF: [u8; 8] = [204, 165, 76, 100, 73, 147, 0, 128];
+
+/// Leaf recording that `player` bought `ticket_number` at `purchased_at`.
+pub fn ticket_leaf(lottery: &Pubkey, player: &Pubkey, ticket_number: u32, purchased_at: i64) -> [u8; 32] {
+    hashv(&[
+        lottery.as_ref(),
+        player.as_ref(),
+        &ticket_number.to_le_bytes(),
+        &purchased_at.to_le_bytes(),
+    ])
+    .to_bytes()
+}
+
+/// Accounts every call to the compression program needs.
+pub struct TicketTree<'a, 'info> {
+    pub compression_program: &'a AccountInfo<'info>,
+    pub merkle_tree: &'a AccountInfo<'info>,
+    pub noop_program: &'a AccountInfo<'info>,
+}
+
+impl<'info> TicketTree<'_, 'info> {
+    /// Initializes the pre-allocated tree with the lottery as its authority.
+    pub fn init(&self, lottery: &Account<'info, Lottery>, max_depth: u32, max_buffer_size: u32) -> Result<()> {
+        let mut data = INIT_EMPTY_MERKLE_TREE.to_vec();
+        data.extend_from_slice(&max_depth.to_le_bytes());
+        data.extend_from_slice(&max_buffer_size.to_le_bytes());
+        self.invoke_as_authority(lottery, data, &[])
+    }
+
+    /// Appends `leaf` as the tree's next leaf.
+    pub fn append(&self, lottery: &Account<'info, Lottery>, leaf: [u8; 32]) -> Result<()> {
+        let mut data = APPEND.to_vec();
+        data.extend_from_slice(&leaf);
+        self.invoke_as_authority(lottery, data, &[])
+    }
+
+    /// Replaces `leaf` at `index` by `new_leaf`, given a `proof` against `root`.
+    pub fn replace(
+        &self,
+        lottery: &Account<'info, Lottery>,
+        proof: &[AccountInfo<'info>],
+        root: [u8; 32],
+        leaf: [u8; 32],
+        new_leaf: [u8; 32],
+        index: u32,
+    ) -> Result<()> {
+        let mut data = REPLACE_LEAF.to_vec();
+        data.extend_from_slice(&root);
+        data.extend_from_slice(&leaf);
+        data.extend_from_slice(&new_leaf);
+        data.extend_from_slice(&index.to_le_bytes());
+        self.invoke_as_authority(lottery, data, proof)
+    }
+
+    /// Fails unless `leaf` is at `index` of the tree, given a `proof` against `root`.
+    pub fn verify(
+        &self,
+        proof: &[AccountInfo<'info>],
+        root: [u8; 32],
+        leaf: [u8; 32],
+        index: u32,
+    ) -> Result<()> {
+        let mut data = VERIFY_LEAF.to_vec();
+        data.extend_from_slice(&root);
+        data.extend_from_slice(&leaf);
+        data.extend_from_slice(&index.to_le_bytes());
+
+        let mut accounts = vec![AccountMeta::new_readonly(self.merkle_tree.key(), false)];
+        accounts.extend(proof.iter().map(|node| AccountMeta::new_readonly(node.key(), false)));
+        let mut infos = vec![self.merkle_tree.clone(), self.compression_program.clone()];
+        infos.extend_from_slice(proof);
+
+        let ix = Instruction {
+            program_id: ACCOUNT_COMPRESSION_ID,
+            accounts,
+            data,
+        };
+        invoke(&ix, &infos).map_err(Into::into)
+    }
+
+    /// Calls the compression program with the tree, the lottery signing as
+    /// its authority, the noop program and any `proof` nodes.
+    fn invoke_as_authority(
+        &self,
+        lottery: &Account<'info, Lottery>,
+        data: Vec<u8>,
+        proof: &[AccountInfo<'info>],
+    ) -> Result<()> {
+        let mut accounts = vec![
+            AccountMeta::new(self.merkle_tree.key(), false),
+            AccountMeta::new_readonly(lottery.key(), true),
+            AccountMeta::new_readonly(self.noop_program.key(), false),
+        ];
+        accounts.extend(proof.iter().map(|node| AccountMeta::new_readonly(node.key(), false)));
+        let mut infos = vec![
+            self.merkle_tree.clone(),
+            lottery.to_account_info(),
+            self.noop_program.clone(),
+            self.compression_program.clone(),
+        ];
+        infos.extend_from_slice(proof);
+
+        let ix = Instruction {
+            program_id: ACCOUNT_COMPRESSION_ID,
+            accounts,
+            data,
+        };
+        lottery.with_signer_seeds(|signer_seeds| invoke_signed(&ix, &infos, signer_seeds))?;
+
+        Ok(())
+    }
+}
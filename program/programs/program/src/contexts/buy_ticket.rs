@@ -1,19 +1,41 @@
 use anchor_lang::prelude::*;
-use crate::state::{Lottery, Ticket};
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::token_2022::Token2022;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+use crate::state::{Lottery, PlatformConfig, PlayerEntry, Ticket, TicketRegistry};
 
 #[derive(Accounts)]
-#[instruction(lottery_id: u64)]
 pub struct BuyTicket<'info> {
     #[account(mut)]
     pub player: Signer<'info>,
     
+    /// Checked against `lottery_id` by the handler, since series rounds are
+    /// seeded by their series and round number instead
+    #[account(mut)]
+    pub lottery: Account<'info, Lottery>,
+
     #[account(
-        mut,
-        seeds = [b"lottery", lottery_id.to_le_bytes().as_ref()],
-        bump = lottery.bump
+        seeds = [b"platform-config"],
+        bump = platform_config.bump
     )]
-    pub lottery: Account<'info, Lottery>,
+    pub platform_config: Account<'info, PlatformConfig>,
     
+    #[account(
+        init_if_needed,
+        payer = player,
+        space = PlayerEntry::space(1),
+        seeds = [b"player-entry", lottery.key().as_ref(), player.key().as_ref()],
+        bump
+    )]
+    pub player_entry: Account<'info, PlayerEntry>,
+
+    #[account(
+        mut,
+        address = lottery.ticket_registry
+    )]
+    pub ticket_registry: AccountLoader<'info, TicketRegistry>,
+
+    /// Picked numbers of a `Pick` lottery ticket, see `buy_numbers`
     #[account(
         init,
         payer = player,
@@ -21,7 +43,102 @@ pub struct BuyTicket<'info> {
         seeds = [b"ticket", lottery.key().as_ref(), lottery.tickets_sold.to_le_bytes().as_ref()],
         bump
     )]
-    pub ticket: Account<'info, Ticket>,
+    pub ticket: Option<Account<'info, Ticket>>,
+
+    /// NFT of the ticket bought from a lottery with `ticket_nfts`, whose
+    /// metadata is added by the handler
+    #[account(
+        init,
+        payer = player,
+        mint::decimals = 0,
+        mint::authority = lottery,
+        mint::token_program = ticket_token_program,
+        extensions::metadata_pointer::authority = lottery,
+        extensions::metadata_pointer::metadata_address = ticket_mint,
+        seeds = [b"ticket-mint", lottery.key().as_ref(), lottery.tickets_sold.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub ticket_mint: Option<Box<InterfaceAccount<'info, Mint>>>,
+
+    /// Receives the ticket NFT
+    #[account(
+        init,
+        payer = player,
+        associated_token::mint = ticket_mint,
+        associated_token::authority = player,
+        associated_token::token_program = ticket_token_program,
+    )]
+    pub ticket_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    pub ticket_token_program: Option<Program<'info, Token2022>>,
+
+    pub associated_token_program: Option<Program<'info, AssociatedToken>>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", lottery.key().as_ref()],
+        bump
+    )]
+    pub vault: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Pays for the tickets of a token lottery
+    #[account(mut)]
+    pub player_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    pub mint: Option<InterfaceAccount<'info, Mint>>,
+
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(lottery_id: u64, recipient: Pubkey)]
+pub struct BuyTicketFor<'info> {
+    /// Pays for the tickets, which go to `recipient`
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+    
+    /// Checked against `lottery_id` by the handler, since series rounds are
+    /// seeded by their series and round number instead
+    #[account(mut)]
+    pub lottery: Account<'info, Lottery>,
+
+    #[account(
+        seeds = [b"platform-config"],
+        bump = platform_config.bump
+    )]
+    pub platform_config: Account<'info, PlatformConfig>,
+    
+    #[account(
+        init_if_needed,
+        payer = buyer,
+        space = PlayerEntry::space(1),
+        seeds = [b"player-entry", lottery.key().as_ref(), recipient.as_ref()],
+        bump
+    )]
+    pub recipient_entry: Account<'info, PlayerEntry>,
+
+    #[account(
+        mut,
+        address = lottery.ticket_registry
+    )]
+    pub ticket_registry: AccountLoader<'info, TicketRegistry>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", lottery.key().as_ref()],
+        bump
+    )]
+    pub vault: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Pays for the tickets of a token lottery
+    #[account(mut)]
+    pub buyer_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    pub mint: Option<InterfaceAccount<'info, Mint>>,
+
+    pub token_program: Option<Interface<'info, TokenInterface>>,
     
     pub system_program: Program<'info, System>,
 }
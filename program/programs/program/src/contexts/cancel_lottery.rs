@@ -0,0 +1,11 @@
+use anchor_lang::prelude::*;
+use crate::state::Lottery;
+
+#[derive(Accounts)]
+pub struct CancelLottery<'info> {
+    /// Either the lottery authority, or anyone once the grace period has passed
+    pub caller: Signer<'info>,
+
+    #[account(mut)]
+    pub lottery: Account<'info, Lottery>,
+}
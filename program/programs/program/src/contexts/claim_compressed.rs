@@ -0,0 +1,61 @@
+use anchor_lang::prelude::*;
+use crate::compression::{ACCOUNT_COMPRESSION_ID, NOOP_ID};
+use crate::errors::LotteryError;
+use crate::state::Lottery;
+
+/// The proof nodes of the ticket's leaf follow as remaining accounts.
+#[derive(Accounts)]
+pub struct ClaimCompressedPrize<'info> {
+    /// The winner, or anyone pushing the prize to them
+    pub claimer: Signer<'info>,
+
+    /// CHECK: Receives the prize; must be the player in the winning ticket's leaf
+    #[account(mut)]
+    pub winner: AccountInfo<'info>,
+
+    #[account(mut)]
+    pub lottery: Account<'info, Lottery>,
+
+    /// CHECK: The lottery's ticket tree, checked by the compression program
+    #[account(
+        constraint = lottery.ticket_tree == Some(merkle_tree.key()) @ LotteryError::InvalidTicketTree
+    )]
+    pub merkle_tree: UncheckedAccount<'info>,
+
+    /// CHECK: The spl-account-compression program
+    #[account(address = ACCOUNT_COMPRESSION_ID)]
+    pub compression_program: UncheckedAccount<'info>,
+
+    /// CHECK: The noop program tree changes are logged through
+    #[account(address = NOOP_ID)]
+    pub noop_program: UncheckedAccount<'info>,
+}
+
+/// The proof nodes of the ticket's leaf follow as remaining accounts.
+#[derive(Accounts)]
+pub struct ClaimCompressedRefund<'info> {
+    /// The player, or anyone pushing the refund to them
+    pub caller: Signer<'info>,
+
+    /// CHECK: Receives the refund; must be the player in the ticket's leaf
+    #[account(mut)]
+    pub player: AccountInfo<'info>,
+
+    #[account(mut)]
+    pub lottery: Account<'info, Lottery>,
+
+    /// CHECK: The lottery's ticket tree, checked by the compression program
+    #[account(
+        mut,
+        constraint = lottery.ticket_tree == Some(merkle_tree.key()) @ LotteryError::InvalidTicketTree
+    )]
+    pub merkle_tree: UncheckedAccount<'info>,
+
+    /// CHECK: The spl-account-compression program
+    #[account(address = ACCOUNT_COMPRESSION_ID)]
+    pub compression_program: UncheckedAccount<'info>,
+
+    /// CHECK: The noop program tree changes are logged through
+    #[account(address = NOOP_ID)]
+    pub noop_program: UncheckedAccount<'info>,
+}
@@ -0,0 +1,65 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+use crate::errors::LotteryError;
+use crate::state::{Lottery, PlayerEntry, Ticket, TicketRegistry};
+
+#[derive(Accounts)]
+pub struct ClaimNumbers<'info> {
+    /// Anyone may register a matching ticket for its prize tier
+    pub caller: Signer<'info>,
+
+    #[account(mut)]
+    pub lottery: Account<'info, Lottery>,
+
+    #[account(
+        mut,
+        has_one = lottery,
+    )]
+    pub ticket: Account<'info, Ticket>,
+}
+
+#[derive(Accounts)]
+pub struct CollectNumbersPrize<'info> {
+    /// The winner, or anyone pushing the prize to them
+    pub claimer: Signer<'info>,
+
+    /// CHECK: Receives the prize and the ticket's rent; must own the ticket
+    #[account(
+        mut,
+        constraint = winner.key() == winner_entry.player @ LotteryError::InvalidWinnerTicket
+    )]
+    pub winner: AccountInfo<'info>,
+
+    #[account(mut)]
+    pub lottery: Account<'info, Lottery>,
+
+    #[account(address = lottery.ticket_registry)]
+    pub ticket_registry: AccountLoader<'info, TicketRegistry>,
+
+    #[account(
+        has_one = lottery,
+    )]
+    pub winner_entry: Account<'info, PlayerEntry>,
+
+    #[account(
+        mut,
+        has_one = lottery,
+        close = winner,
+    )]
+    pub ticket: Account<'info, Ticket>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", lottery.key().as_ref()],
+        bump
+    )]
+    pub vault: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Receives the prize of a token lottery
+    #[account(mut)]
+    pub winner_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    pub mint: Option<InterfaceAccount<'info, Mint>>,
+
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+}
@@ -0,0 +1,44 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+use crate::state::{Lottery, PlayerEntry, TicketRegistry};
+
+#[derive(Accounts)]
+pub struct ClaimPrize<'info> {
+    /// The winner, or anyone pushing the prize to them
+    pub claimer: Signer<'info>,
+
+    /// CHECK: Receives the prize; must own the winning entry, or hold the
+    /// winning ticket NFT of a lottery with `ticket_nfts`
+    #[account(mut)]
+    pub winner: AccountInfo<'info>,
+
+    #[account(mut)]
+    pub lottery: Account<'info, Lottery>,
+
+    #[account(address = lottery.ticket_registry)]
+    pub ticket_registry: AccountLoader<'info, TicketRegistry>,
+
+    /// Omitted for lotteries with `ticket_nfts`
+    #[account(
+        has_one = lottery,
+    )]
+    pub winner_entry: Option<Account<'info, PlayerEntry>>,
+
+    /// Winner's account holding the winning ticket NFT
+    pub ticket_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", lottery.key().as_ref()],
+        bump
+    )]
+    pub vault: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Receives the prize of a token lottery
+    #[account(mut)]
+    pub winner_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    pub mint: Option<InterfaceAccount<'info, Mint>>,
+
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+}
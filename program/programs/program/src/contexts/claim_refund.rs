@@ -0,0 +1,89 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_2022::Token2022;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+use crate::state::{Lottery, PlayerEntry, TicketRegistry};
+
+#[derive(Accounts)]
+pub struct ClaimRefund<'info> {
+    #[account(mut)]
+    pub player: Signer<'info>,
+
+    #[account(mut)]
+    pub lottery: Account<'info, Lottery>,
+
+    #[account(
+        mut,
+        has_one = player,
+        has_one = lottery,
+        close = player,
+    )]
+    pub player_entry: Account<'info, PlayerEntry>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", lottery.key().as_ref()],
+        bump
+    )]
+    pub vault: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Receives the refund of a token lottery
+    #[account(mut)]
+    pub player_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    pub mint: Option<InterfaceAccount<'info, Mint>>,
+
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+}
+
+#[derive(Accounts)]
+#[instruction(ticket_number: u32)]
+pub struct ClaimNftRefund<'info> {
+    /// Holder of the ticket's NFT, which is burned for the refund
+    #[account(mut)]
+    pub holder: Signer<'info>,
+
+    #[account(mut)]
+    pub lottery: Account<'info, Lottery>,
+
+    #[account(address = lottery.ticket_registry)]
+    pub ticket_registry: AccountLoader<'info, TicketRegistry>,
+
+    /// Entry of the ticket's buyer, which records what the ticket cost
+    #[account(
+        mut,
+        has_one = lottery,
+    )]
+    pub buyer_entry: Account<'info, PlayerEntry>,
+
+    #[account(
+        mut,
+        seeds = [b"ticket-mint", lottery.key().as_ref(), ticket_number.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub ticket_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mut,
+        token::mint = ticket_mint,
+        token::authority = holder,
+        token::token_program = ticket_token_program,
+    )]
+    pub ticket_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    pub ticket_token_program: Program<'info, Token2022>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", lottery.key().as_ref()],
+        bump
+    )]
+    pub vault: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Receives the refund of a token lottery
+    #[account(mut)]
+    pub holder_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    pub mint: Option<InterfaceAccount<'info, Mint>>,
+
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+}
@@ -0,0 +1,40 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+use crate::state::{Lottery, TicketRegistry};
+
+#[derive(Accounts)]
+pub struct CloseLottery<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        has_one = authority,
+        close = authority,
+    )]
+    pub lottery: Account<'info, Lottery>,
+
+    #[account(
+        mut,
+        address = lottery.ticket_registry,
+        close = authority,
+    )]
+    pub ticket_registry: AccountLoader<'info, TicketRegistry>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", lottery.key().as_ref()],
+        bump
+    )]
+    pub vault: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Receives tokens left in the vault, such as ones sent to it directly
+    #[account(mut)]
+    pub authority_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Collects transfer fees withheld in the vault before it closes
+    #[account(mut)]
+    pub mint: Option<InterfaceAccount<'info, Mint>>,
+
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+}
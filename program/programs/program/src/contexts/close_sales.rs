@@ -0,0 +1,12 @@
+use anchor_lang::prelude::*;
+use crate::state::Lottery;
+
+#[derive(Accounts)]
+pub struct CloseSales<'info> {
+    /// Anyone may close sales once the lottery has expired or sold out, and
+    /// is paid a share of the crank reward when the lottery settles
+    pub caller: Signer<'info>,
+
+    #[account(mut)]
+    pub lottery: Account<'info, Lottery>,
+}
@@ -0,0 +1,40 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+use crate::state::{Lottery, PlayerEntry};
+
+#[derive(Accounts)]
+pub struct CloseTicket<'info> {
+    /// Anyone may close a settled entry, so one absent player cannot keep
+    /// the lottery from closing
+    pub caller: Signer<'info>,
+
+    /// CHECK: Owner of the entry; receives its rent and any refund
+    #[account(mut)]
+    pub player: AccountInfo<'info>,
+
+    #[account(mut)]
+    pub lottery: Account<'info, Lottery>,
+
+    #[account(
+        mut,
+        has_one = player,
+        has_one = lottery,
+        close = player,
+    )]
+    pub player_entry: Account<'info, PlayerEntry>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", lottery.key().as_ref()],
+        bump
+    )]
+    pub vault: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Receives the refund of a token lottery
+    #[account(mut)]
+    pub player_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    pub mint: Option<InterfaceAccount<'info, Mint>>,
+
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+}
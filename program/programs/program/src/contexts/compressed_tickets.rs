@@ -0,0 +1,61 @@
+use anchor_lang::prelude::*;
+use crate::compression::{ACCOUNT_COMPRESSION_ID, NOOP_ID};
+use crate::errors::LotteryError;
+use crate::state::{Lottery, PlatformConfig};
+
+#[derive(Accounts)]
+pub struct InitTicketTree<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        has_one = authority,
+    )]
+    pub lottery: Account<'info, Lottery>,
+
+    /// CHECK: Allocated by the authority and owned by the compression program,
+    /// which checks it while initializing
+    #[account(mut)]
+    pub merkle_tree: UncheckedAccount<'info>,
+
+    /// CHECK: The spl-account-compression program
+    #[account(address = ACCOUNT_COMPRESSION_ID)]
+    pub compression_program: UncheckedAccount<'info>,
+
+    /// CHECK: The noop program tree changes are logged through
+    #[account(address = NOOP_ID)]
+    pub noop_program: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct BuyCompressedTickets<'info> {
+    #[account(mut)]
+    pub player: Signer<'info>,
+
+    /// Checked against `lottery_id` by the handler
+    #[account(mut)]
+    pub lottery: Account<'info, Lottery>,
+
+    #[account(
+        seeds = [b"platform-config"],
+        bump = platform_config.bump
+    )]
+    pub platform_config: Account<'info, PlatformConfig>,
+
+    /// CHECK: The lottery's ticket tree, checked by the compression program
+    #[account(
+        mut,
+        constraint = lottery.ticket_tree == Some(merkle_tree.key()) @ LotteryError::InvalidTicketTree
+    )]
+    pub merkle_tree: UncheckedAccount<'info>,
+
+    /// CHECK: The spl-account-compression program
+    #[account(address = ACCOUNT_COMPRESSION_ID)]
+    pub compression_program: UncheckedAccount<'info>,
+
+    /// CHECK: The noop program tree changes are logged through
+    #[account(address = NOOP_ID)]
+    pub noop_program: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
@@ -0,0 +1,50 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+use crate::errors::LotteryError;
+use crate::state::{Lottery, PlatformConfig};
+
+#[derive(Accounts)]
+pub struct ExpirePrizes<'info> {
+    /// Anyone may settle prizes once the claim period has ended
+    pub caller: Signer<'info>,
+
+    #[account(mut)]
+    pub lottery: Account<'info, Lottery>,
+
+    /// CHECK: The lottery creator, paid under the `Creator` policy
+    #[account(
+        mut,
+        constraint = lottery_creator.key() == lottery.authority
+    )]
+    pub lottery_creator: AccountInfo<'info>,
+
+    #[account(
+        seeds = [b"platform-config"],
+        bump = platform_config.bump
+    )]
+    pub platform_config: Account<'info, PlatformConfig>,
+
+    /// CHECK: The configured fee wallet, paid under the `Platform` policy
+    #[account(
+        mut,
+        constraint = platform_fee_account.key() == platform_config.fee_wallet @ LotteryError::InvalidFeeWallet
+    )]
+    pub platform_fee_account: AccountInfo<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", lottery.key().as_ref()],
+        bump
+    )]
+    pub vault: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(mut)]
+    pub creator_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(mut)]
+    pub platform_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    pub mint: Option<InterfaceAccount<'info, Mint>>,
+
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+}
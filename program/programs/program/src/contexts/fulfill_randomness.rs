@@ -1,14 +1,11 @@
 use anchor_lang::prelude::*;
-use crate::state::{Lottery};
+use crate::state::Lottery;
 
 #[derive(Accounts)]
 pub struct FulfillRandomness<'info> {
+    /// PDA of the configured oracle program, only obtainable as a CPI signer
+    pub oracle_authority: Signer<'info>,
+
     #[account(mut)]
-    pub authority: Signer<'info>,
-    
-    #[account(
-        mut,
-        has_one = authority,
-    )]
     pub lottery: Account<'info, Lottery>,
 }
@@ -1,5 +1,6 @@
 use anchor_lang::prelude::*;
-use crate::state::Lottery;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+use crate::state::{Lottery, PlatformConfig, TicketRegistry};
 
 #[derive(Accounts)]
 #[instruction(lottery_id: u64)]
@@ -16,5 +17,36 @@ pub struct InitializeLottery<'info> {
     )]
     pub lottery: Account<'info, Lottery>,
 
+    #[account(
+        init,
+        payer = authority,
+        space = TicketRegistry::space(0),
+        seeds = [b"ticket-registry", lottery.key().as_ref()],
+        bump
+    )]
+    pub ticket_registry: AccountLoader<'info, TicketRegistry>,
+
+    #[account(
+        seeds = [b"platform-config"],
+        bump = platform_config.bump
+    )]
+    pub platform_config: Account<'info, PlatformConfig>,
+
+    /// Token the lottery is denominated in; omit for a SOL lottery
+    pub mint: Option<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        init,
+        payer = authority,
+        token::mint = mint,
+        token::authority = lottery,
+        token::token_program = token_program,
+        seeds = [b"vault", lottery.key().as_ref()],
+        bump
+    )]
+    pub vault: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+
     pub system_program: Program<'info, System>,
 }
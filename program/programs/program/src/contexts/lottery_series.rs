@@ -0,0 +1,135 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+use crate::errors::LotteryError;
+use crate::state::{Jackpot, Lottery, LotterySeries, PlatformConfig, TicketRegistry};
+
+#[derive(Accounts)]
+#[instruction(series_id: u64)]
+pub struct InitializeSeries<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = LotterySeries::INIT_SPACE,
+        seeds = [b"series", series_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub series: Account<'info, LotterySeries>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = Jackpot::INIT_SPACE,
+        seeds = [b"jackpot", series.key().as_ref()],
+        bump
+    )]
+    pub jackpot: Account<'info, Jackpot>,
+
+    #[account(
+        seeds = [b"platform-config"],
+        bump = platform_config.bump
+    )]
+    pub platform_config: Account<'info, PlatformConfig>,
+
+    /// Token every round is denominated in; omit for SOL rounds
+    pub mint: Option<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        init,
+        payer = authority,
+        token::mint = mint,
+        token::authority = jackpot,
+        token::token_program = token_program,
+        seeds = [b"vault", jackpot.key().as_ref()],
+        bump
+    )]
+    pub jackpot_vault: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct OpenRound<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        has_one = authority,
+    )]
+    pub series: Account<'info, LotterySeries>,
+
+    /// The series' latest round; omit when opening the first one
+    #[account(
+        mut,
+        constraint = previous_round.key() == series.current_round @ LotteryError::InvalidPreviousRound
+    )]
+    pub previous_round: Option<Account<'info, Lottery>>,
+
+    #[account(
+        mut,
+        seeds = [b"jackpot", series.key().as_ref()],
+        bump = jackpot.bump
+    )]
+    pub jackpot: Account<'info, Jackpot>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = Lottery::INIT_SPACE,
+        seeds = [b"lottery", series.key().as_ref(), series.rounds.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub lottery: Account<'info, Lottery>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = TicketRegistry::space(0),
+        seeds = [b"ticket-registry", lottery.key().as_ref()],
+        bump
+    )]
+    pub ticket_registry: AccountLoader<'info, TicketRegistry>,
+
+    #[account(
+        seeds = [b"platform-config"],
+        bump = platform_config.bump
+    )]
+    pub platform_config: Account<'info, PlatformConfig>,
+
+    pub mint: Option<InterfaceAccount<'info, Mint>>,
+
+    /// Vault of the previous round, holding the funds to carry over
+    #[account(
+        mut,
+        seeds = [b"vault", series.current_round.as_ref()],
+        bump
+    )]
+    pub previous_vault: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", jackpot.key().as_ref()],
+        bump
+    )]
+    pub jackpot_vault: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        init,
+        payer = authority,
+        token::mint = mint,
+        token::authority = lottery,
+        token::token_program = token_program,
+        seeds = [b"vault", lottery.key().as_ref()],
+        bump
+    )]
+    pub vault: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+
+    pub system_program: Program<'info, System>,
+}
@@ -1,42 +1,94 @@
 use anchor_lang::prelude::*;
-use crate::state::{Lottery, Ticket};
-use crate::PLATFORM_FEE_WALLET;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+use crate::state::{jackpot_vault_address, Jackpot, Lottery, PlatformConfig};
+use crate::errors::LotteryError;
 
 #[derive(Accounts)]
 pub struct Payout<'info> {
+    /// Anyone may settle the lottery and collect its share of the crank
+    /// reward, along with the shares of steps no keeper was recorded for
     #[account(mut)]
-    pub authority: Signer<'info>,
+    pub caller: Signer<'info>,
     
     #[account(
         mut,
-        has_one = authority,
+        constraint = !lottery.fees_paid @ LotteryError::AlreadyPaidOut,
     )]
     pub lottery: Account<'info, Lottery>,
     
+    /// CHECK: This is the lottery creator's account that will receive the creator share
     #[account(
-        constraint = winner_ticket.lottery == lottery.key(),
-        constraint = winner_ticket.ticket_number == lottery.winner.unwrap()
+        mut,
+        constraint = lottery_creator.key() == lottery.authority
     )]
-    pub winner_ticket: Account<'info, Ticket>,
+    pub lottery_creator: AccountInfo<'info>,
     
-    /// CHECK: This is the winner's account that will receive the payout
+    #[account(
+        seeds = [b"platform-config"],
+        bump = platform_config.bump
+    )]
+    pub platform_config: Account<'info, PlatformConfig>,
+
+    /// CHECK: This is the platform fee account - must be the configured fee wallet
     #[account(
         mut,
-        constraint = winner.key() == winner_ticket.player
+        constraint = platform_fee_account.key() == platform_config.fee_wallet @ LotteryError::InvalidFeeWallet
     )]
-    pub winner: AccountInfo<'info>,
-    
-    /// CHECK: This is the lottery creator's account that will receive 5% fee
+    pub platform_fee_account: AccountInfo<'info>,
+
+    /// Series jackpot collecting the prizes of tiers nobody won
     #[account(
         mut,
-        constraint = lottery_creator.key() == lottery.authority
+        constraint = lottery.series == Some(jackpot.series) @ LotteryError::InvalidJackpot
     )]
-    pub lottery_creator: AccountInfo<'info>,
-    
-    /// CHECK: This is the platform fee account - must be the designated platform wallet
+    pub jackpot: Option<Account<'info, Jackpot>>,
+
+    /// Vault of the series' jackpot, for token lotteries
     #[account(
         mut,
-        constraint = platform_fee_account.key() == PLATFORM_FEE_WALLET
+        constraint = lottery.series.map(|series| jackpot_vault_address(&series))
+            == Some(jackpot_vault.key()) @ LotteryError::InvalidJackpot
     )]
-    pub platform_fee_account: AccountInfo<'info>,
+    pub jackpot_vault: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", lottery.key().as_ref()],
+        bump
+    )]
+    pub vault: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// CHECK: Keeper recorded as having closed sales, paid its crank share
+    #[account(
+        mut,
+        constraint = lottery.sales_closer == Some(sales_closer.key()) @ LotteryError::InvalidKeeper
+    )]
+    pub sales_closer: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Keeper recorded as having requested the draw, paid its crank share
+    #[account(
+        mut,
+        constraint = lottery.draw_requester == Some(draw_requester.key()) @ LotteryError::InvalidKeeper
+    )]
+    pub draw_requester: Option<UncheckedAccount<'info>>,
+
+    /// Associated token accounts receiving the shares of a token lottery
+    #[account(mut)]
+    pub caller_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(mut)]
+    pub creator_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(mut)]
+    pub platform_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(mut)]
+    pub sales_closer_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    #[account(mut)]
+    pub draw_requester_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    pub mint: Option<InterfaceAccount<'info, Mint>>,
+
+    pub token_program: Option<Interface<'info, TokenInterface>>,
 }
@@ -0,0 +1,58 @@
+use anchor_lang::prelude::*;
+use crate::errors::LotteryError;
+use crate::program::Lottery as LotteryProgram;
+use crate::state::PlatformConfig;
+
+#[derive(Accounts)]
+pub struct InitializePlatform<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(
+        init,
+        payer = admin,
+        space = PlatformConfig::INIT_SPACE,
+        seeds = [b"platform-config"],
+        bump
+    )]
+    pub platform_config: Account<'info, PlatformConfig>,
+
+    #[account(constraint = program.programdata_address()? == Some(program_data.key()))]
+    pub program: Program<'info, LotteryProgram>,
+
+    // Only the upgrade authority can claim the singleton config
+    #[account(
+        constraint = program_data.upgrade_authority_address == Some(admin.key())
+            @ LotteryError::Unauthorized
+    )]
+    pub program_data: Account<'info, ProgramData>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdatePlatformConfig<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"platform-config"],
+        bump = platform_config.bump,
+        has_one = admin @ LotteryError::Unauthorized,
+    )]
+    pub platform_config: Account<'info, PlatformConfig>,
+}
+
+#[derive(Accounts)]
+pub struct AcceptPlatformAdmin<'info> {
+    /// The admin nominated by `transfer_platform_admin`
+    pub new_admin: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"platform-config"],
+        bump = platform_config.bump,
+        constraint = platform_config.pending_admin == Some(new_admin.key()) @ LotteryError::Unauthorized,
+    )]
+    pub platform_config: Account<'info, PlatformConfig>,
+}
@@ -0,0 +1,12 @@
+use anchor_lang::prelude::*;
+use crate::state::Lottery;
+
+#[derive(Accounts)]
+pub struct RequestRandomness<'info> {
+    /// Anyone may request the draw once sales are closed, and is paid a
+    /// share of the crank reward when the lottery settles
+    pub caller: Signer<'info>,
+
+    #[account(mut)]
+    pub lottery: Account<'info, Lottery>,
+}
@@ -0,0 +1,30 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::sysvar::slot_hashes;
+use crate::state::Lottery;
+
+#[derive(Accounts)]
+pub struct RevealRandomness<'info> {
+    /// Anyone holding the committed secret may reveal it
+    pub caller: Signer<'info>,
+
+    #[account(mut)]
+    pub lottery: Account<'info, Lottery>,
+
+    /// CHECK: SlotHashes is too large to deserialize on-chain, it is read manually
+    #[account(address = slot_hashes::ID)]
+    pub slot_hashes: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct RecordSlotHash<'info> {
+    /// Anyone may record the reveal slot's hash before SlotHashes drops it,
+    /// earning the draw's share of the crank reward
+    pub caller: Signer<'info>,
+
+    #[account(mut)]
+    pub lottery: Account<'info, Lottery>,
+
+    /// CHECK: SlotHashes is too large to deserialize on-chain, it is read manually
+    #[account(address = slot_hashes::ID)]
+    pub slot_hashes: UncheckedAccount<'info>,
+}
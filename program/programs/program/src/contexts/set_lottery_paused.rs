@@ -0,0 +1,18 @@
+use anchor_lang::prelude::*;
+use crate::errors::LotteryError;
+use crate::state::{Lottery, PlatformConfig};
+
+#[derive(Accounts)]
+pub struct SetLotteryPaused<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        seeds = [b"platform-config"],
+        bump = platform_config.bump,
+        has_one = admin @ LotteryError::Unauthorized,
+    )]
+    pub platform_config: Account<'info, PlatformConfig>,
+
+    #[account(mut)]
+    pub lottery: Account<'info, Lottery>,
+}
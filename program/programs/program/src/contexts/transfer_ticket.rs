@@ -0,0 +1,54 @@
+use anchor_lang::prelude::*;
+use crate::state::{Lottery, PlatformConfig, PlayerEntry, TicketRegistry};
+
+#[derive(Accounts)]
+#[instruction(ticket_number: u32, recipient: Pubkey)]
+pub struct TransferTicket<'info> {
+    #[account(mut)]
+    pub holder: Signer<'info>,
+
+    #[account(mut)]
+    pub lottery: Account<'info, Lottery>,
+
+    #[account(
+        seeds = [b"platform-config"],
+        bump = platform_config.bump
+    )]
+    pub platform_config: Account<'info, PlatformConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"player-entry", lottery.key().as_ref(), holder.key().as_ref()],
+        bump = holder_entry.bump
+    )]
+    pub holder_entry: Account<'info, PlayerEntry>,
+
+    /// Created on the recipient's first ticket, paid for by the holder
+    #[account(
+        init_if_needed,
+        payer = holder,
+        space = PlayerEntry::space(1),
+        seeds = [b"player-entry", lottery.key().as_ref(), recipient.as_ref()],
+        bump
+    )]
+    pub recipient_entry: Account<'info, PlayerEntry>,
+
+    #[account(
+        mut,
+        address = lottery.ticket_registry
+    )]
+    pub ticket_registry: AccountLoader<'info, TicketRegistry>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetTransferLock<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        has_one = authority,
+    )]
+    pub lottery: Account<'info, Lottery>,
+}
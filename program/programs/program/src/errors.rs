@@ -34,4 +34,202 @@ pub enum LotteryError {
 
         #[msg("Lottery has expired/closed")]
     LotteryClosed, 
+
+    #[msg("Lottery is not open for ticket sales")]
+    LotteryNotOpen,
+
+    #[msg("Ticket sales are still open")]
+    SalesStillOpen,
+
+    #[msg("Ticket sales have not been closed")]
+    SalesNotClosed,
+
+    #[msg("Winner has not been drawn")]
+    WinnerNotDrawn,
+
+    #[msg("Lottery has been cancelled")]
+    LotteryCancelled,
+
+    #[msg("Lottery has not been cancelled")]
+    LotteryNotCancelled,
+
+    #[msg("Lottery cannot be cancelled once a winner is drawn")]
+    CannotCancelAfterDraw,
+
+    #[msg("Lottery cannot be cancelled by this caller yet")]
+    CancelGracePeriodActive,
+
+    #[msg("Revealed secret does not match the commitment")]
+    InvalidReveal,
+
+    #[msg("Reveal slot has not been reached yet")]
+    RevealTooEarly,
+
+    #[msg("Reveal window has expired")]
+    RevealWindowExpired,
+
+    #[msg("Slot hash not found in SlotHashes sysvar")]
+    SlotHashNotFound,
+
+    #[msg("Lottery uses a different randomness source")]
+    WrongRandomnessSource,
+
+    #[msg("Randomness has not been requested")]
+    RandomnessNotRequested,
+
+    #[msg("Caller is not the configured randomness oracle")]
+    UnauthorizedOracle,
+
+    #[msg("Oracle did not answer before the timeout")]
+    OracleTimeout,
+
+    #[msg("Lottery has already been paid out")]
+    AlreadyPaidOut,
+
+    #[msg("Prize shares must add up to 10000 basis points")]
+    InvalidPrizeSplit,
+
+    #[msg("Platform share is below the minimum")]
+    PlatformShareTooLow,
+
+    #[msg("Invalid platform configuration")]
+    InvalidPlatformConfig,
+
+    #[msg("Signer is not allowed to perform this action")]
+    Unauthorized,
+
+    #[msg("Ticket price is outside the platform limits")]
+    TicketPriceOutOfRange,
+
+    #[msg("Duration is outside the platform limits")]
+    DurationOutOfRange,
+
+    #[msg("Account is not the platform fee wallet")]
+    InvalidFeeWallet,
+
+    #[msg("Program is paused")]
+    ProgramPaused,
+
+    #[msg("Lottery is paused")]
+    LotteryPaused,
+
+    #[msg("Ticket count must be greater than zero")]
+    InvalidTicketCount,
+
+    #[msg("Arithmetic overflow")]
+    MathOverflow,
+
+    #[msg("Lottery has not been paid out or fully refunded")]
+    LotteryNotSettled,
+
+    #[msg("Player entries must be closed before the lottery")]
+    EntriesStillOpen,
+
+    #[msg("Lottery balance does not cover the prize pool above its rent-exempt minimum")]
+    InsufficientPrizePool,
+
+    #[msg("Account balance is below its rent-exempt minimum")]
+    BelowRentExemption,
+
+    #[msg("Token lotteries require their vault, token program and token accounts")]
+    MissingTokenAccounts,
+
+    #[msg("Token account is not the expected associated token account")]
+    InvalidTokenAccount,
+
+    #[msg("Token account or mint does not match the lottery's mint")]
+    InvalidMint,
+
+    #[msg("Mint has an extension that would prevent settlement")]
+    UnsupportedMintExtension,
+
+    #[msg("Prize tiers must be 1-10 non-zero shares adding up to 10000 basis points")]
+    InvalidPrizeTiers,
+
+    #[msg("No prize was drawn for this tier")]
+    InvalidPrizeTier,
+
+    #[msg("Prize has already been claimed")]
+    PrizeAlreadyClaimed,
+
+    #[msg("Prize claim period has ended")]
+    ClaimPeriodExpired,
+
+    #[msg("Prizes can still be claimed")]
+    ClaimPeriodActive,
+
+    #[msg("Every prize has already been claimed")]
+    NoUnclaimedPrizes,
+
+    #[msg("Account is not the series' latest round")]
+    InvalidPreviousRound,
+
+    #[msg("The previous round has not been settled")]
+    PreviousRoundNotSettled,
+
+    #[msg("Account is not the lottery series' jackpot")]
+    InvalidJackpot,
+
+    #[msg("Pick lotteries need 1-10 picks, no more than their highest number, and no more tiers than picks")]
+    InvalidPickConfig,
+
+    #[msg("Instruction does not apply to this lottery's draw mode")]
+    WrongDrawMode,
+
+    #[msg("Picked numbers must be distinct and within the lottery's range")]
+    InvalidNumbers,
+
+    #[msg("Ticket does not match enough drawn numbers")]
+    NotEnoughMatches,
+
+    #[msg("Ticket is already registered for a prize")]
+    TicketAlreadyRegistered,
+
+    #[msg("Ticket has not been registered for a prize")]
+    TicketNotRegistered,
+
+    #[msg("Pick lottery tickets need their ticket account")]
+    MissingTicketAccount,
+
+    #[msg("Tickets of this lottery can no longer be transferred")]
+    TransfersClosed,
+
+    #[msg("Ticket is not held by this entry")]
+    TicketNotOwned,
+
+    #[msg("Cannot transfer a ticket to its current holder")]
+    SelfTransfer,
+
+    #[msg("NFT lotteries sell one ticket at a time with its NFT accounts, other lotteries take none")]
+    InvalidTicketNft,
+
+    #[msg("Tickets of this lottery are NFTs and change hands as tokens")]
+    TicketsAreNfts,
+
+    #[msg("Token account does not hold the winning ticket NFT")]
+    InvalidTicketHolder,
+
+    #[msg("Instruction does not apply to how this lottery stores its tickets")]
+    WrongTicketStorage,
+
+    #[msg("Merkle tree is not the lottery's ticket tree or cannot hold all its tickets")]
+    InvalidTicketTree,
+
+    #[msg("Oracle program is not approved by the platform")]
+    OracleNotApproved,
+
+    #[msg("Reveal slot hash has already been recorded")]
+    SlotHashAlreadyRecorded,
+
+    #[msg("Tickets of an NFT lottery are refunded to their NFT holders")]
+    RefundGoesToNftHolder,
+
+    #[msg("Ticket NFTs bought through this entry have not all been refunded")]
+    NftRefundsPending,
+
+    #[msg("Account of a keeper owed a share of the crank reward is missing")]
+    MissingKeeper,
+
+    #[msg("Account is not the keeper recorded on the lottery")]
+    InvalidKeeper,
 }
\ No newline at end of file
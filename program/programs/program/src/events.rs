@@ -0,0 +1,46 @@
+use anchor_lang::prelude::*;
+
+#[event]
+pub struct PlatformPauseChanged {
+    pub admin: Pubkey,
+    pub paused: bool,
+}
+
+#[event]
+pub struct LotteryPauseChanged {
+    pub admin: Pubkey,
+    pub lottery: Pubkey,
+    pub paused: bool,
+}
+
+#[event]
+pub struct TicketTransferred {
+    pub lottery: Pubkey,
+    pub ticket_number: u32,
+    pub from: Pubkey,
+    pub to: Pubkey,
+}
+
+#[event]
+pub struct TicketsGifted {
+    pub lottery: Pubkey,
+    pub buyer: Pubkey,
+    pub recipient: Pubkey,
+    pub first_ticket: u32,
+    pub count: u32,
+}
+
+#[event]
+pub struct TransferLockChanged {
+    pub lottery: Pubkey,
+    pub locked_at_close: bool,
+}
+
+#[event]
+pub struct CompressedTicketsBought {
+    pub lottery: Pubkey,
+    pub player: Pubkey,
+    pub first_ticket: u32,
+    pub count: u32,
+    pub purchased_at: i64,
+}
@@ -1,66 +1,436 @@
 use anchor_lang::prelude::*;
-use crate::contexts::BuyTicket;
+use anchor_spl::token_interface::spl_token_metadata_interface::state::TokenMetadata;
+use anchor_spl::token_interface::{self, Mint, TokenAccount, TokenInterface};
+use anchor_spl::token_2022::spl_token_2022::instruction::AuthorityType;
+use crate::accounting::{ensure_pool_covered, vault_balance, Treasury};
+use crate::contexts::{BuyTicket, BuyTicketFor};
 use crate::errors::LotteryError;
+use crate::events::TicketsGifted;
+use crate::state::{DrawMode, Lottery, LotteryState, PlatformConfig, PlayerEntry, Ticket, TicketRegistry};
+use crate::MAX_TICKETS_PER_PURCHASE;
 
 pub fn buy_ticket_handler(
     ctx: Context<BuyTicket>,
     lottery_id: u64,
 ) -> Result<()> {
-    let lottery = &mut ctx.accounts.lottery;
-    let player = &ctx.accounts.player;
-    let ticket = &mut ctx.accounts.ticket;
-
-    require!(lottery.lottery_id == lottery_id, LotteryError::InvalidLotteryId);
-
-    // Check if lottery expired by time
-    // Use i128 arithmetic to avoid incorrect casting from u64 -> i64 which can
-    // reinterpret the bits and produce huge/negative values. Convert both
-    // created_at (i64) and duration (u64) to i128 for safe addition/comparison.
-    let now = Clock::get()?.unix_timestamp;
-    let now_i128 = now as i128;
-    let created_i128 = lottery.created_at as i128;
-    let duration_i128 = lottery.duration as i128;
-
-    let expiry_i128 = created_i128.checked_add(duration_i128).ok_or(LotteryError::LotteryClosed)?;
-    if now_i128 >= expiry_i128 {
-        return err!(LotteryError::LotteryClosed);
+    buy_tickets_handler(ctx, lottery_id, 1)
+}
+
+/// Buys one ticket of a `Pick` lottery with the player's chosen `numbers`.
+pub fn buy_numbers_handler(
+    ctx: Context<BuyTicket>,
+    lottery_id: u64,
+    numbers: Vec<u8>,
+) -> Result<()> {
+    let lottery = &ctx.accounts.lottery;
+    let numbers = lottery.draw_mode.check_picks(&numbers)?;
+    let ticket_number = lottery.tickets_sold;
+    let lottery_key = lottery.key();
+    let bump = ctx.bumps.ticket;
+
+    let ticket = ctx
+        .accounts
+        .ticket
+        .as_mut()
+        .ok_or(LotteryError::MissingTicketAccount)?;
+    ticket.lottery = lottery_key;
+    ticket.ticket_number = ticket_number;
+    ticket.numbers = numbers;
+    ticket.tier = None;
+    ticket.bump = bump.ok_or(LotteryError::MissingTicketAccount)?;
+
+    msg!("Ticket {} picks {:?}", ticket_number, ticket.numbers);
+
+    buy_tickets_handler(ctx, lottery_id, 1)
+}
+
+/// Buys `count` consecutive ticket numbers and records them on the player's
+/// `PlayerEntry`, creating it on the first purchase.
+pub fn buy_tickets_handler(
+    ctx: Context<BuyTicket>,
+    lottery_id: u64,
+    count: u32,
+) -> Result<()> {
+    let accounts = ctx.accounts;
+    let player = accounts.player.key();
+    let first_ticket = Purchase {
+        buyer: &accounts.player,
+        lottery: &mut accounts.lottery,
+        platform_config: &accounts.platform_config,
+        entry: &mut accounts.player_entry,
+        ticket_registry: &accounts.ticket_registry,
+        ticket: accounts.ticket.as_ref(),
+        ticket_nft: accounts.ticket_mint.is_some(),
+        vault: accounts.vault.as_ref(),
+        buyer_token_account: accounts.player_token_account.as_ref(),
+        mint: accounts.mint.as_ref(),
+        token_program: accounts.token_program.as_ref(),
+        system_program: &accounts.system_program,
     }
+    .execute(player, ctx.bumps.player_entry, lottery_id, count)?;
 
-    require!(lottery.tickets_sold < lottery.max_tickets, LotteryError::LotteryFull);
+    if accounts.lottery.ticket_nfts {
+        mint_ticket_nft(accounts, first_ticket)?;
+    }
 
-    // Transfer ticket price from player to lottery account
-    let cpi_ctx = CpiContext::new(
-        ctx.accounts.system_program.to_account_info(),
-        anchor_lang::system_program::Transfer {
-            from: player.to_account_info(),
-            to: lottery.to_account_info(),
-        },
+    msg!(
+        "Tickets {}..{} purchased by {} for lottery {}",
+        first_ticket,
+        first_ticket + count,
+        player,
+        lottery_id
     );
-    
-    anchor_lang::system_program::transfer(cpi_ctx, lottery.ticket_price)?;
-
-    // Initialize ticket
-    ticket.lottery = lottery.key();
-    ticket.player = player.key();
-    ticket.ticket_number = lottery.tickets_sold;
-    ticket.purchased_at = now;
-    ticket.bump = ctx.bumps.ticket;
-
-    // Update lottery state
-    lottery.tickets_sold += 1;
-    lottery.total_prize_pool += lottery.ticket_price;
-
-    // If max tickets reached → close buying
-    if lottery.tickets_sold == lottery.max_tickets {
-        msg!("All tickets sold! Lottery {} is now waiting for randomness.", lottery_id);
+
+    Ok(())
+}
+
+/// Buys one ticket as a gift, recorded on the `recipient`'s `PlayerEntry`.
+pub fn buy_ticket_for_handler(
+    ctx: Context<BuyTicketFor>,
+    lottery_id: u64,
+    recipient: Pubkey,
+) -> Result<()> {
+    let accounts = ctx.accounts;
+    require!(!accounts.lottery.ticket_nfts, LotteryError::TicketsAreNfts);
+
+    let first_ticket = Purchase {
+        buyer: &accounts.buyer,
+        lottery: &mut accounts.lottery,
+        platform_config: &accounts.platform_config,
+        entry: &mut accounts.recipient_entry,
+        ticket_registry: &accounts.ticket_registry,
+        ticket: None,
+        ticket_nft: false,
+        vault: accounts.vault.as_ref(),
+        buyer_token_account: accounts.buyer_token_account.as_ref(),
+        mint: accounts.mint.as_ref(),
+        token_program: accounts.token_program.as_ref(),
+        system_program: &accounts.system_program,
     }
+    .execute(recipient, ctx.bumps.recipient_entry, lottery_id, 1)?;
+
+    emit!(TicketsGifted {
+        lottery: accounts.lottery.key(),
+        buyer: accounts.buyer.key(),
+        recipient,
+        first_ticket,
+        count: 1,
+    });
 
     msg!(
-        "Ticket {} purchased by {} for lottery {}",
-        ticket.ticket_number,
-        player.key(),
+        "Ticket {} bought by {} for {} in lottery {}",
+        first_ticket,
+        accounts.buyer.key(),
+        recipient,
         lottery_id
     );
 
     Ok(())
 }
+
+/// Accounts of a purchase, shared by `BuyTicket` and `BuyTicketFor`.
+struct Purchase<'a, 'info> {
+    buyer: &'a Signer<'info>,
+    lottery: &'a mut Account<'info, Lottery>,
+    platform_config: &'a Account<'info, PlatformConfig>,
+    entry: &'a mut Account<'info, PlayerEntry>,
+    ticket_registry: &'a AccountLoader<'info, TicketRegistry>,
+    ticket: Option<&'a Account<'info, Ticket>>,
+    /// Whether the accounts of a ticket NFT were given
+    ticket_nft: bool,
+    vault: Option<&'a InterfaceAccount<'info, TokenAccount>>,
+    buyer_token_account: Option<&'a InterfaceAccount<'info, TokenAccount>>,
+    mint: Option<&'a InterfaceAccount<'info, Mint>>,
+    token_program: Option<&'a Interface<'info, TokenInterface>>,
+    system_program: &'a Program<'info, System>,
+}
+
+impl Purchase<'_, '_> {
+    /// Charges the buyer for `count` consecutive ticket numbers and records
+    /// them for `owner` on the entry, initializing it with `entry_bump` on
+    /// the owner's first ticket. Returns the first ticket number.
+    fn execute(self, owner: Pubkey, entry_bump: u8, lottery_id: u64, count: u32) -> Result<u32> {
+        let Purchase { buyer, lottery, entry, .. } = self;
+
+        require!(lottery.lottery_id == lottery_id, LotteryError::InvalidLotteryId);
+        require!(!self.platform_config.paused, LotteryError::ProgramPaused);
+        require!(!lottery.paused, LotteryError::LotteryPaused);
+        require!(lottery.state != LotteryState::Cancelled, LotteryError::LotteryCancelled);
+        require!(lottery.state == LotteryState::Open, LotteryError::LotteryNotOpen);
+        require!(lottery.ticket_tree.is_none(), LotteryError::WrongTicketStorage);
+
+        // Pick tickets are sold one at a time through `buy_numbers`
+        let picks_ready = match (lottery.draw_mode, self.ticket) {
+            (DrawMode::Pick { picks, .. }, Some(ticket)) => {
+                count == 1 && ticket.numbers.len() == picks as usize
+            }
+            (DrawMode::Pick { .. }, None) => false,
+            (_, ticket) => ticket.is_none(),
+        };
+        require!(picks_ready, LotteryError::WrongDrawMode);
+        require!(
+            lottery.ticket_nfts == self.ticket_nft && (!self.ticket_nft || count == 1),
+            LotteryError::InvalidTicketNft
+        );
+
+        // Check if lottery expired by time
+        let now = Clock::get()?.unix_timestamp;
+        if lottery.has_expired(now)? {
+            return err!(LotteryError::LotteryClosed);
+        }
+
+        require!(
+            count > 0 && count <= MAX_TICKETS_PER_PURCHASE,
+            LotteryError::InvalidTicketCount
+        );
+        let new_tickets_sold = lottery
+            .tickets_sold
+            .checked_add(count)
+            .ok_or(LotteryError::MathOverflow)?;
+        require!(new_tickets_sold <= lottery.max_tickets, LotteryError::LotteryFull);
+
+        let cost = lottery
+            .ticket_price
+            .checked_mul(count as u64)
+            .ok_or(LotteryError::MathOverflow)?;
+
+        // Transfer ticket price for every ticket from the buyer to the lottery's funds.
+        // A transfer-fee mint delivers less than `cost`, so only what reached the
+        // vault is added to the pool.
+        let received = match Treasury::of(lottery, self.vault, self.mint, self.token_program)? {
+            None => {
+                let cpi_ctx = CpiContext::new(
+                    self.system_program.to_account_info(),
+                    anchor_lang::system_program::Transfer {
+                        from: buyer.to_account_info(),
+                        to: lottery.to_account_info(),
+                    },
+                );
+
+                anchor_lang::system_program::transfer(cpi_ctx, cost)?;
+                cost
+            }
+            Some(treasury) => {
+                let buyer_token_account = self
+                    .buyer_token_account
+                    .ok_or(LotteryError::MissingTokenAccounts)?;
+                let vault_before = treasury.vault.amount;
+
+                let cpi_ctx = CpiContext::new(
+                    treasury.token_program.to_account_info(),
+                    token_interface::TransferChecked {
+                        from: buyer_token_account.to_account_info(),
+                        mint: treasury.mint.to_account_info(),
+                        to: treasury.vault.to_account_info(),
+                        authority: buyer.to_account_info(),
+                    },
+                );
+
+                token_interface::transfer_checked(cpi_ctx, cost, treasury.mint.decimals)?;
+                vault_balance(&treasury.vault.to_account_info())?
+                    .checked_sub(vault_before)
+                    .ok_or(LotteryError::MathOverflow)?
+            }
+        };
+
+        let first_ticket = lottery.tickets_sold;
+        let registry_info = self.ticket_registry.to_account_info();
+        grow_account(
+            &registry_info,
+            &buyer.to_account_info(),
+            &self.system_program.to_account_info(),
+            TicketRegistry::space(new_tickets_sold),
+        )?;
+
+        {
+            let mut ticket_registry = self.ticket_registry.load_mut()?;
+            open_entry(entry, lottery, &mut ticket_registry, owner, entry_bump)?;
+            ticket_registry.ticket_count = new_tickets_sold;
+        }
+        TicketRegistry::assign(
+            &mut registry_info.try_borrow_mut_data()?,
+            first_ticket,
+            count,
+            entry.index,
+        );
+
+        if entry.needs_new_range(first_ticket) {
+            grow_account(
+                &entry.to_account_info(),
+                &buyer.to_account_info(),
+                &self.system_program.to_account_info(),
+                PlayerEntry::space(entry.ranges.len() + 1),
+            )?;
+        }
+        entry.record(first_ticket, count)?;
+        entry.total_spent = entry
+            .total_spent
+            .checked_add(received)
+            .ok_or(LotteryError::MathOverflow)?;
+
+        // Update lottery state
+        lottery.tickets_sold = new_tickets_sold;
+        lottery.total_prize_pool = lottery
+            .total_prize_pool
+            .checked_add(received)
+            .ok_or(LotteryError::MathOverflow)?;
+        let treasury = Treasury::of(lottery, self.vault, self.mint, self.token_program)?;
+        ensure_pool_covered(lottery, treasury.as_ref(), lottery.total_prize_pool)?;
+
+        // If max tickets reached → close buying
+        if lottery.is_sold_out() {
+            lottery.close_sales(&Clock::get()?)?;
+            msg!("All tickets sold! Lottery {} is now waiting for randomness.", lottery_id);
+        }
+
+        Ok(first_ticket)
+    }
+}
+
+/// Adds metadata to the NFT of `ticket_number`, mints it to the player and
+/// drops the mint authority so no second copy can ever exist.
+fn mint_ticket_nft(accounts: &BuyTicket, ticket_number: u32) -> Result<()> {
+    let lottery = &accounts.lottery;
+    let (Some(ticket_mint), Some(ticket_token_account), Some(token_program)) = (
+        accounts.ticket_mint.as_ref(),
+        accounts.ticket_token_account.as_ref(),
+        accounts.ticket_token_program.as_ref(),
+    ) else {
+        return err!(LotteryError::InvalidTicketNft);
+    };
+
+    let name = format!("Lottery {} Ticket #{}", lottery.lottery_id, ticket_number);
+    let symbol = String::from("TICKET");
+    let uri = String::new();
+
+    // Token-2022 grows the mint to fit the metadata but expects its rent to be there already
+    let mint_info = ticket_mint.to_account_info();
+    let metadata_len = TokenMetadata {
+        name: name.clone(),
+        symbol: symbol.clone(),
+        uri: uri.clone(),
+        ..Default::default()
+    }
+    .tlv_size_of()?;
+    let rent_due = Rent::get()?
+        .minimum_balance(mint_info.data_len() + metadata_len)
+        .saturating_sub(mint_info.lamports());
+    if rent_due > 0 {
+        let cpi_ctx = CpiContext::new(
+            accounts.system_program.to_account_info(),
+            anchor_lang::system_program::Transfer {
+                from: accounts.player.to_account_info(),
+                to: mint_info.clone(),
+            },
+        );
+        anchor_lang::system_program::transfer(cpi_ctx, rent_due)?;
+    }
+
+    lottery.with_signer_seeds(|signer_seeds| {
+        let program = token_program.to_account_info();
+        token_interface::token_metadata_initialize(
+            CpiContext::new_with_signer(
+                program.clone(),
+                token_interface::TokenMetadataInitialize {
+                    program_id: program.clone(),
+                    metadata: mint_info.clone(),
+                    update_authority: lottery.to_account_info(),
+                    mint_authority: lottery.to_account_info(),
+                    mint: mint_info.clone(),
+                },
+                signer_seeds,
+            ),
+            name,
+            symbol,
+            uri,
+        )?;
+        token_interface::mint_to(
+            CpiContext::new_with_signer(
+                program.clone(),
+                token_interface::MintTo {
+                    mint: mint_info.clone(),
+                    to: ticket_token_account.to_account_info(),
+                    authority: lottery.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            1,
+        )?;
+        token_interface::set_authority(
+            CpiContext::new_with_signer(
+                program,
+                token_interface::SetAuthority {
+                    current_authority: lottery.to_account_info(),
+                    account_or_mint: mint_info.clone(),
+                },
+                signer_seeds,
+            ),
+            AuthorityType::MintTokens,
+            None,
+        )
+    })?;
+
+    msg!("Minted NFT {} for ticket {}", ticket_mint.key(), ticket_number);
+
+    Ok(())
+}
+
+/// Initializes `entry` for `player` on their first ticket, giving them the
+/// next owner index of the lottery's registry.
+pub(crate) fn open_entry(
+    entry: &mut Account<PlayerEntry>,
+    lottery: &mut Account<Lottery>,
+    ticket_registry: &mut TicketRegistry,
+    player: Pubkey,
+    bump: u8,
+) -> Result<()> {
+    if entry.player != Pubkey::default() {
+        return Ok(());
+    }
+
+    entry.lottery = lottery.key();
+    entry.player = player;
+    entry.index = ticket_registry.player_count;
+    entry.bump = bump;
+    ticket_registry.player_count = ticket_registry
+        .player_count
+        .checked_add(1)
+        .ok_or(LotteryError::MathOverflow)?;
+    lottery.open_entries = lottery
+        .open_entries
+        .checked_add(1)
+        .ok_or(LotteryError::MathOverflow)?;
+
+    Ok(())
+}
+
+/// Resizes `account` to at least `new_len` bytes, topping up its rent from
+/// the player.
+pub(crate) fn grow_account<'info>(
+    account: &AccountInfo<'info>,
+    player: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    new_len: usize,
+) -> Result<()> {
+    if account.data_len() >= new_len {
+        return Ok(());
+    }
+
+    let rent_due = Rent::get()?
+        .minimum_balance(new_len)
+        .saturating_sub(account.lamports());
+    if rent_due > 0 {
+        let cpi_ctx = CpiContext::new(
+            system_program.clone(),
+            anchor_lang::system_program::Transfer {
+                from: player.clone(),
+                to: account.clone(),
+            },
+        );
+        anchor_lang::system_program::transfer(cpi_ctx, rent_due)?;
+    }
+
+    account.resize(new_len)?;
+
+    Ok(())
+}
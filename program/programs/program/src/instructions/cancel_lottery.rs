@@ -0,0 +1,62 @@
+use anchor_lang::prelude::*;
+use crate::contexts::CancelLottery;
+use crate::errors::LotteryError;
+use crate::state::{LotteryState, RandomnessSource};
+use crate::CANCEL_GRACE_PERIOD;
+
+pub fn cancel_lottery_handler(ctx: Context<CancelLottery>) -> Result<()> {
+    let lottery = &mut ctx.accounts.lottery;
+    let caller = &ctx.accounts.caller;
+
+    match lottery.state {
+        LotteryState::Open | LotteryState::SalesClosed | LotteryState::AwaitingRandomness => {}
+        LotteryState::Cancelled => return err!(LotteryError::LotteryCancelled),
+        LotteryState::WinnerDrawn | LotteryState::PaidOut => {
+            return err!(LotteryError::CannotCancelAfterDraw)
+        }
+    }
+
+    // Once sales close the authority could predict the draw, so from then on
+    // it has no more say than anyone else
+    let authority_may_cancel =
+        caller.key() == lottery.authority && lottery.state == LotteryState::Open;
+
+    if !authority_may_cancel {
+        let clock = Clock::get()?;
+        let randomness_missed = match (lottery.state, lottery.randomness_source) {
+            (LotteryState::SalesClosed, RandomnessSource::CommitReveal { .. }) => {
+                clock.unix_timestamp > lottery.reveal_deadline
+            }
+            (LotteryState::AwaitingRandomness, RandomnessSource::Oracle { .. }) => {
+                clock.slot > lottery.oracle_deadline()
+            }
+            _ => false,
+        };
+
+        // Anyone can rescue the pool if the authority never settles the lottery
+        let grace_end = lottery
+            .sales_end()?
+            .checked_add(CANCEL_GRACE_PERIOD as i128)
+            .ok_or(LotteryError::LotteryClosed)?;
+        let grace_passed = clock.unix_timestamp as i128 >= grace_end;
+
+        // With no tickets sold there is nothing to draw or refund
+        let unsold = lottery.tickets_sold == 0 && lottery.has_expired(clock.unix_timestamp)?;
+
+        require!(
+            randomness_missed || grace_passed || unsold,
+            LotteryError::CancelGracePeriodActive
+        );
+    }
+
+    lottery.cancel()?;
+
+    msg!(
+        "Lottery {} cancelled by {}. {} tickets are refundable.",
+        lottery.lottery_id,
+        caller.key(),
+        lottery.tickets_sold
+    );
+
+    Ok(())
+}
@@ -0,0 +1,108 @@
+use anchor_lang::prelude::*;
+use crate::accounting::{ensure_pool_covered, pay_from_lottery, settlement_pool};
+use crate::compression::{ticket_leaf, TicketTree, EMPTY_LEAF};
+use crate::contexts::{ClaimCompressedPrize, ClaimCompressedRefund};
+use crate::errors::LotteryError;
+use crate::state::LotteryState;
+
+/// Pays prize tier `tier` to the player in the winning ticket's leaf, given
+/// the leaf's `purchased_at` and a proof against `root` as remaining
+/// accounts. Anyone may call it, like `claim_prize`.
+pub fn claim_compressed_prize_handler<'info>(
+    ctx: Context<'_, '_, 'info, 'info, ClaimCompressedPrize<'info>>,
+    tier: u8,
+    root: [u8; 32],
+    purchased_at: i64,
+) -> Result<()> {
+    let lottery = &mut ctx.accounts.lottery;
+    let winner = &ctx.accounts.winner;
+    let tier = tier as usize;
+
+    require!(lottery.state == LotteryState::WinnerDrawn, LotteryError::WinnerNotDrawn);
+    let winning_ticket = *lottery
+        .winners
+        .get(tier)
+        .ok_or(LotteryError::InvalidPrizeTier)?;
+    require!(lottery.is_tier_won(tier), LotteryError::NoWinner);
+    require!(!lottery.is_tier_claimed(tier), LotteryError::PrizeAlreadyClaimed);
+
+    let now = Clock::get()?.unix_timestamp;
+    require!(now <= lottery.claim_deadline, LotteryError::ClaimPeriodExpired);
+
+    TicketTree {
+        compression_program: &ctx.accounts.compression_program,
+        merkle_tree: &ctx.accounts.merkle_tree,
+        noop_program: &ctx.accounts.noop_program,
+    }
+    .verify(
+        ctx.remaining_accounts,
+        root,
+        ticket_leaf(&lottery.key(), &winner.key(), winning_ticket, purchased_at),
+        winning_ticket,
+    )?;
+
+    // Compressed tickets are only sold for SOL
+    let pool = settlement_pool(lottery, None)?;
+    lottery.lock_payouts(pool)?;
+    ensure_pool_covered(lottery, None, lottery.total_prize_pool)?;
+
+    let amount = lottery.tier_payout(tier)?;
+    pay_from_lottery(lottery, None, winner, amount)?;
+    lottery.record_claim(tier, amount, now)?;
+
+    msg!(
+        "Tier {} prize of {} claimed by {} with compressed ticket {}",
+        tier + 1,
+        amount,
+        winner.key(),
+        winning_ticket
+    );
+
+    Ok(())
+}
+
+/// Refunds compressed ticket `ticket_number` of a cancelled lottery to the
+/// player in its leaf, replacing the leaf by the empty leaf so the ticket
+/// cannot be refunded again.
+pub fn claim_compressed_refund_handler<'info>(
+    ctx: Context<'_, '_, 'info, 'info, ClaimCompressedRefund<'info>>,
+    ticket_number: u32,
+    root: [u8; 32],
+    purchased_at: i64,
+) -> Result<()> {
+    let lottery = &mut ctx.accounts.lottery;
+    let player = &ctx.accounts.player;
+
+    require!(lottery.state == LotteryState::Cancelled, LotteryError::LotteryNotCancelled);
+
+    TicketTree {
+        compression_program: &ctx.accounts.compression_program,
+        merkle_tree: &ctx.accounts.merkle_tree,
+        noop_program: &ctx.accounts.noop_program,
+    }
+    .replace(
+        lottery,
+        ctx.remaining_accounts,
+        root,
+        ticket_leaf(&lottery.key(), &player.key(), ticket_number, purchased_at),
+        EMPTY_LEAF,
+        ticket_number,
+    )?;
+
+    let refund_amount = lottery.ticket_price;
+    lottery.total_prize_pool = lottery
+        .total_prize_pool
+        .checked_sub(refund_amount)
+        .ok_or(LotteryError::InvalidPayout)?;
+    pay_from_lottery(lottery, None, player, refund_amount)?;
+
+    msg!(
+        "Refunded {} lamports to {} for compressed ticket {} of lottery {}",
+        refund_amount,
+        player.key(),
+        ticket_number,
+        lottery.lottery_id
+    );
+
+    Ok(())
+}
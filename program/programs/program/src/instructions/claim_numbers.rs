@@ -0,0 +1,119 @@
+use anchor_lang::prelude::*;
+use crate::accounting::{ensure_pool_covered, pay_from_lottery, payee, settlement_pool, Treasury};
+use crate::contexts::{ClaimNumbers, CollectNumbersPrize};
+use crate::errors::LotteryError;
+use crate::state::{DrawMode, LotteryState, TicketRegistry};
+
+/// Checks a `Pick` ticket against the drawn numbers and registers it for
+/// the prize tier it matches. Registration closes with the claim period.
+pub fn claim_numbers_handler(ctx: Context<ClaimNumbers>) -> Result<()> {
+    let lottery = &mut ctx.accounts.lottery;
+    let ticket = &mut ctx.accounts.ticket;
+
+    require!(lottery.state == LotteryState::WinnerDrawn, LotteryError::WinnerNotDrawn);
+    let DrawMode::Pick { picks, .. } = lottery.draw_mode else {
+        return err!(LotteryError::WrongDrawMode);
+    };
+    let now = Clock::get()?.unix_timestamp;
+    require!(now <= lottery.claim_deadline, LotteryError::ClaimPeriodExpired);
+    require!(ticket.tier.is_none(), LotteryError::TicketAlreadyRegistered);
+
+    let matches = ticket
+        .numbers
+        .iter()
+        .filter(|number| lottery.winning_numbers.contains(number))
+        .count();
+    let tier = picks as usize - matches;
+    let tier_winners = lottery
+        .tier_winners
+        .get_mut(tier)
+        .ok_or(LotteryError::NotEnoughMatches)?;
+    *tier_winners = tier_winners
+        .checked_add(1)
+        .ok_or(LotteryError::MathOverflow)?;
+    ticket.tier = Some(tier as u8);
+
+    msg!(
+        "Ticket {} matched {} of {:?} and is registered for tier {}",
+        ticket.ticket_number,
+        matches,
+        lottery.winning_numbers,
+        tier + 1
+    );
+
+    Ok(())
+}
+
+/// Pays a registered `Pick` ticket its share of its prize tier once
+/// registration has closed, and closes the ticket.
+pub fn collect_numbers_prize_handler(ctx: Context<CollectNumbersPrize>) -> Result<()> {
+    let lottery = &mut ctx.accounts.lottery;
+    let ticket = &ctx.accounts.ticket;
+
+    require!(lottery.state == LotteryState::WinnerDrawn, LotteryError::WinnerNotDrawn);
+    require!(
+        matches!(lottery.draw_mode, DrawMode::Pick { .. }),
+        LotteryError::WrongDrawMode
+    );
+    let now = Clock::get()?.unix_timestamp;
+    require!(now > lottery.claim_deadline, LotteryError::ClaimPeriodActive);
+    require!(now <= lottery.prize_deadline()?, LotteryError::ClaimPeriodExpired);
+    let tier = ticket.tier.ok_or(LotteryError::TicketNotRegistered)? as usize;
+
+    // Resolve the ticket's owner straight from the registry
+    let owner_index = {
+        let registry_data = ctx.accounts.ticket_registry.as_ref().try_borrow_data()?;
+        TicketRegistry::owner_of(&registry_data, ticket.ticket_number)
+            .ok_or(LotteryError::InvalidWinnerTicket)?
+    };
+    require!(
+        ctx.accounts.winner_entry.index == owner_index,
+        LotteryError::InvalidWinnerTicket
+    );
+
+    let treasury = Treasury::of(
+        lottery,
+        ctx.accounts.vault.as_ref(),
+        ctx.accounts.mint.as_ref(),
+        ctx.accounts.token_program.as_ref(),
+    )?;
+    let treasury = treasury.as_ref();
+    let pool = settlement_pool(lottery, treasury)?;
+    lottery.lock_payouts(pool)?;
+    ensure_pool_covered(lottery, treasury, lottery.total_prize_pool)?;
+
+    // The last ticket paid in a tier takes its rounding dust
+    let payments = lottery.tier_payments[tier]
+        .checked_add(1)
+        .ok_or(LotteryError::MathOverflow)?;
+    let last = payments == lottery.tier_winners[tier];
+    let amount = if last {
+        lottery.tier_remaining(tier)?
+    } else {
+        lottery.tier_share(tier)?
+    };
+
+    let to = payee(
+        treasury,
+        &ctx.accounts.winner,
+        ctx.accounts.winner_token_account.as_ref(),
+    )?;
+    pay_from_lottery(lottery, treasury, &to, amount)?;
+
+    lottery.tier_payments[tier] = payments;
+    if last {
+        lottery.record_claim(tier, amount, now)?;
+    } else {
+        lottery.record_payment(amount, now)?;
+    }
+
+    msg!(
+        "Tier {} share of {} collected by {} with ticket {}",
+        tier + 1,
+        amount,
+        ctx.accounts.winner.key(),
+        ticket.ticket_number
+    );
+
+    Ok(())
+}
@@ -0,0 +1,89 @@
+use anchor_lang::prelude::*;
+use crate::accounting::{ensure_pool_covered, pay_from_lottery, payee, settlement_pool, Treasury};
+use crate::contexts::ClaimPrize;
+use crate::errors::LotteryError;
+use crate::state::{ticket_mint_address, DrawMode, LotteryState, TicketRegistry};
+
+/// Pays prize tier `tier` to the holder of its winning ticket. Anyone may
+/// call it, so the authority can still push prizes to winners.
+pub fn claim_prize_handler(ctx: Context<ClaimPrize>, tier: u8) -> Result<()> {
+    let lottery = &mut ctx.accounts.lottery;
+    let tier = tier as usize;
+
+    require!(lottery.state == LotteryState::WinnerDrawn, LotteryError::WinnerNotDrawn);
+    require!(
+        !matches!(lottery.draw_mode, DrawMode::Pick { .. }),
+        LotteryError::WrongDrawMode
+    );
+    let winning_ticket = *lottery
+        .winners
+        .get(tier)
+        .ok_or(LotteryError::InvalidPrizeTier)?;
+    require!(lottery.is_tier_won(tier), LotteryError::NoWinner);
+    require!(!lottery.is_tier_claimed(tier), LotteryError::PrizeAlreadyClaimed);
+
+    let now = Clock::get()?.unix_timestamp;
+    require!(now <= lottery.claim_deadline, LotteryError::ClaimPeriodExpired);
+
+    let winner = ctx.accounts.winner.key();
+    if lottery.ticket_nfts {
+        // Whoever holds the ticket's NFT now is owed its prize
+        let holder = ctx
+            .accounts
+            .ticket_token_account
+            .as_ref()
+            .ok_or(LotteryError::InvalidTicketHolder)?;
+        require!(
+            holder.mint == ticket_mint_address(&lottery.key(), winning_ticket)
+                && holder.owner == winner
+                && holder.amount == 1,
+            LotteryError::InvalidTicketHolder
+        );
+    } else {
+        let winner_entry = ctx
+            .accounts
+            .winner_entry
+            .as_ref()
+            .ok_or(LotteryError::InvalidWinnerTicket)?;
+        require_keys_eq!(winner_entry.player, winner, LotteryError::InvalidWinnerTicket);
+
+        // Resolve the winning ticket straight from the registry
+        let owner_index = {
+            let registry_data = ctx.accounts.ticket_registry.as_ref().try_borrow_data()?;
+            TicketRegistry::owner_of(&registry_data, winning_ticket)
+                .ok_or(LotteryError::InvalidWinnerTicket)?
+        };
+        require!(winner_entry.index == owner_index, LotteryError::InvalidWinnerTicket);
+    }
+
+    let treasury = Treasury::of(
+        lottery,
+        ctx.accounts.vault.as_ref(),
+        ctx.accounts.mint.as_ref(),
+        ctx.accounts.token_program.as_ref(),
+    )?;
+    let treasury = treasury.as_ref();
+    let pool = settlement_pool(lottery, treasury)?;
+    lottery.lock_payouts(pool)?;
+    ensure_pool_covered(lottery, treasury, lottery.total_prize_pool)?;
+
+    let amount = lottery.tier_payout(tier)?;
+    let to = payee(
+        treasury,
+        &ctx.accounts.winner,
+        ctx.accounts.winner_token_account.as_ref(),
+    )?;
+    pay_from_lottery(lottery, treasury, &to, amount)?;
+
+    lottery.record_claim(tier, amount, now)?;
+
+    msg!(
+        "Tier {} prize of {} claimed by {} with ticket {}",
+        tier + 1,
+        amount,
+        ctx.accounts.winner.key(),
+        winning_ticket
+    );
+
+    Ok(())
+}
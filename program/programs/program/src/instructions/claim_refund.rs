@@ -0,0 +1,136 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{self, TokenAccount};
+use crate::accounting::{pay_from_lottery, payee, Treasury};
+use crate::contexts::{ClaimNftRefund, ClaimRefund};
+use crate::errors::LotteryError;
+use crate::instructions::close_pick_tickets;
+use crate::state::{Lottery, LotteryState, PlayerEntry, TicketRegistry};
+
+/// Refunds a cancelled entry and closes it, along with the `Ticket` accounts
+/// of a `Pick` lottery passed as remaining accounts.
+pub fn claim_refund_handler<'info>(
+    ctx: Context<'_, '_, 'info, 'info, ClaimRefund<'info>>,
+) -> Result<()> {
+    let lottery = &mut ctx.accounts.lottery;
+    let player_entry = &ctx.accounts.player_entry;
+
+    require!(lottery.state == LotteryState::Cancelled, LotteryError::LotteryNotCancelled);
+    // The buyer may have sold the ticket since
+    require!(!lottery.ticket_nfts, LotteryError::RefundGoesToNftHolder);
+
+    let refund_amount = refund_entry(
+        lottery,
+        &ctx.accounts.player.to_account_info(),
+        player_entry,
+        Treasury::of(
+            lottery,
+            ctx.accounts.vault.as_ref(),
+            ctx.accounts.mint.as_ref(),
+            ctx.accounts.token_program.as_ref(),
+        )?
+        .as_ref(),
+        ctx.accounts.player_token_account.as_ref(),
+    )?;
+    close_pick_tickets(
+        lottery,
+        player_entry,
+        &ctx.accounts.player.to_account_info(),
+        ctx.remaining_accounts,
+    )?;
+    lottery.open_entries = lottery
+        .open_entries
+        .checked_sub(1)
+        .ok_or(LotteryError::MathOverflow)?;
+
+    msg!(
+        "Refunded {} lamports to {} for {} tickets of lottery {}",
+        refund_amount,
+        ctx.accounts.player.key(),
+        player_entry.ticket_count,
+        lottery.lottery_id
+    );
+
+    Ok(())
+}
+
+/// Refunds ticket `ticket_number` of a cancelled `ticket_nfts` lottery to
+/// whoever holds its NFT, burning the NFT. The ticket is worth its share of
+/// what its buyer's entry paid.
+pub fn claim_nft_refund_handler(ctx: Context<ClaimNftRefund>, ticket_number: u32) -> Result<()> {
+    let lottery = &mut ctx.accounts.lottery;
+    let buyer_entry = &mut ctx.accounts.buyer_entry;
+    let holder = &ctx.accounts.holder;
+
+    require!(lottery.state == LotteryState::Cancelled, LotteryError::LotteryNotCancelled);
+    require!(
+        ctx.accounts.ticket_token_account.amount == 1,
+        LotteryError::InvalidTicketHolder
+    );
+
+    let owner_index = {
+        let registry_data = ctx.accounts.ticket_registry.as_ref().try_borrow_data()?;
+        TicketRegistry::owner_of(&registry_data, ticket_number).ok_or(LotteryError::TicketNotOwned)?
+    };
+    require!(buyer_entry.index == owner_index, LotteryError::TicketNotOwned);
+
+    // Burning the NFT keeps the ticket from being refunded twice
+    let cpi_ctx = CpiContext::new(
+        ctx.accounts.ticket_token_program.to_account_info(),
+        token_interface::Burn {
+            mint: ctx.accounts.ticket_mint.to_account_info(),
+            from: ctx.accounts.ticket_token_account.to_account_info(),
+            authority: holder.to_account_info(),
+        },
+    );
+    token_interface::burn(cpi_ctx, 1)?;
+
+    let refund_amount = buyer_entry.refund_one()?;
+    lottery.total_prize_pool = lottery
+        .total_prize_pool
+        .checked_sub(refund_amount)
+        .ok_or(LotteryError::InvalidPayout)?;
+
+    let treasury = Treasury::of(
+        lottery,
+        ctx.accounts.vault.as_ref(),
+        ctx.accounts.mint.as_ref(),
+        ctx.accounts.token_program.as_ref(),
+    )?;
+    let to = payee(
+        treasury.as_ref(),
+        &holder.to_account_info(),
+        ctx.accounts.holder_token_account.as_ref(),
+    )?;
+    pay_from_lottery(lottery, treasury.as_ref(), &to, refund_amount)?;
+
+    msg!(
+        "Refunded {} to {} for ticket NFT {} of lottery {}",
+        refund_amount,
+        holder.key(),
+        ticket_number,
+        lottery.lottery_id
+    );
+
+    Ok(())
+}
+
+/// Returns what the player paid for `player_entry` out of the prize pool.
+/// The entry's own rent comes back when the caller closes it.
+pub(crate) fn refund_entry<'info>(
+    lottery: &mut Account<'info, Lottery>,
+    player: &AccountInfo<'info>,
+    player_entry: &PlayerEntry,
+    treasury: Option<&Treasury<'_, 'info>>,
+    player_token_account: Option<&InterfaceAccount<'info, TokenAccount>>,
+) -> Result<u64> {
+    let refund_amount = player_entry.total_spent;
+    lottery.total_prize_pool = lottery
+        .total_prize_pool
+        .checked_sub(refund_amount)
+        .ok_or(LotteryError::InvalidPayout)?;
+
+    let to = payee(treasury, player, player_token_account)?;
+    pay_from_lottery(lottery, treasury, &to, refund_amount)?;
+
+    Ok(refund_amount)
+}
@@ -0,0 +1,73 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_2022::spl_token_2022::extension::ExtensionType;
+use anchor_spl::token_interface;
+use crate::accounting::{mint_extensions, pay_from_lottery, payee, vault_balance, Treasury};
+use crate::contexts::CloseLottery;
+use crate::errors::LotteryError;
+
+pub fn close_lottery_handler(ctx: Context<CloseLottery>) -> Result<()> {
+    let lottery = &ctx.accounts.lottery;
+
+    require!(lottery.is_settled(), LotteryError::LotteryNotSettled);
+    // Entries point at this lottery's address, which a new lottery with the
+    // same id would reuse, so they must all be gone first
+    require!(lottery.open_entries == 0, LotteryError::EntriesStillOpen);
+
+    // A token lottery's vault is emptied by settlement and closes with it
+    if let Some(treasury) = Treasury::of(
+        lottery,
+        ctx.accounts.vault.as_ref(),
+        ctx.accounts.mint.as_ref(),
+        ctx.accounts.token_program.as_ref(),
+    )? {
+        // Fees withheld in the vault would block closing it
+        let mint_info = treasury.mint.to_account_info();
+        if mint_extensions(&mint_info)?.contains(&ExtensionType::TransferFeeConfig) {
+            let cpi_ctx = CpiContext::new(
+                treasury.token_program.to_account_info(),
+                token_interface::HarvestWithheldTokensToMint {
+                    token_program_id: treasury.token_program.to_account_info(),
+                    mint: mint_info,
+                },
+            );
+            token_interface::harvest_withheld_tokens_to_mint(
+                cpi_ctx,
+                vec![treasury.vault.to_account_info()],
+            )?;
+        }
+
+        // Anyone can send tokens to the vault after the pool is fixed, and a
+        // token account only closes once empty
+        let leftover = vault_balance(&treasury.vault.to_account_info())?;
+        if leftover > 0 {
+            let to = payee(
+                Some(&treasury),
+                &ctx.accounts.authority.to_account_info(),
+                ctx.accounts.authority_token_account.as_ref(),
+            )?;
+            pay_from_lottery(lottery, Some(&treasury), &to, leftover)?;
+            msg!("Swept {} tokens left in the vault", leftover);
+        }
+
+        lottery.with_signer_seeds(|signer_seeds| {
+            let cpi_ctx = CpiContext::new_with_signer(
+                treasury.token_program.to_account_info(),
+                token_interface::CloseAccount {
+                    account: treasury.vault.to_account_info(),
+                    destination: ctx.accounts.authority.to_account_info(),
+                    authority: lottery.to_account_info(),
+                },
+                signer_seeds,
+            );
+            token_interface::close_account(cpi_ctx)
+        })?;
+    }
+
+    msg!(
+        "Closed lottery {}, rent returned to {}",
+        lottery.lottery_id,
+        ctx.accounts.authority.key()
+    );
+
+    Ok(())
+}
@@ -0,0 +1,30 @@
+use anchor_lang::prelude::*;
+use crate::contexts::CloseSales;
+use crate::errors::LotteryError;
+use crate::state::LotteryState;
+
+pub fn close_sales_handler(ctx: Context<CloseSales>) -> Result<()> {
+    let lottery = &mut ctx.accounts.lottery;
+
+    require!(lottery.state != LotteryState::Cancelled, LotteryError::LotteryCancelled);
+    require!(lottery.state == LotteryState::Open, LotteryError::LotteryNotOpen);
+
+    // Sales can only be closed early once every ticket is gone
+    let clock = Clock::get()?;
+    require!(
+        lottery.has_expired(clock.unix_timestamp)? || lottery.is_sold_out(),
+        LotteryError::SalesStillOpen
+    );
+
+    lottery.close_sales(&clock)?;
+    lottery.sales_closer = Some(ctx.accounts.caller.key());
+
+    msg!(
+        "Ticket sales closed for lottery {} with {} tickets sold. Reveal due after slot {}",
+        lottery.lottery_id,
+        lottery.tickets_sold,
+        lottery.reveal_slot
+    );
+
+    Ok(())
+}
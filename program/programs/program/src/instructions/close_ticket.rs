@@ -0,0 +1,84 @@
+use anchor_lang::prelude::*;
+use crate::accounting::Treasury;
+use crate::contexts::CloseTicket;
+use crate::errors::LotteryError;
+use crate::instructions::refund_entry;
+use crate::state::{Lottery, LotteryState, PlayerEntry, Ticket};
+
+/// Closes a settled entry, returning its rent to the player whoever the
+/// caller is. The `Ticket` accounts of the entry's numbers in a
+/// `Pick` lottery follow as remaining accounts and are closed with it.
+pub fn close_ticket_handler<'info>(
+    ctx: Context<'_, '_, 'info, 'info, CloseTicket<'info>>,
+) -> Result<()> {
+    let lottery = &mut ctx.accounts.lottery;
+    let player_entry = &ctx.accounts.player_entry;
+
+    match lottery.state {
+        LotteryState::PaidOut => {}
+        // NFT holders claim the refunds of the tickets bought through the entry
+        LotteryState::Cancelled if lottery.ticket_nfts => {
+            require!(player_entry.ticket_count == 0, LotteryError::NftRefundsPending);
+        }
+        // Closing a cancelled entry also settles its refund
+        LotteryState::Cancelled => {
+            refund_entry(
+                lottery,
+                &ctx.accounts.player,
+                player_entry,
+                Treasury::of(
+                    lottery,
+                    ctx.accounts.vault.as_ref(),
+                    ctx.accounts.mint.as_ref(),
+                    ctx.accounts.token_program.as_ref(),
+                )?
+                .as_ref(),
+                ctx.accounts.player_token_account.as_ref(),
+            )?;
+        }
+        _ => return err!(LotteryError::LotteryNotSettled),
+    }
+    close_pick_tickets(
+        lottery,
+        player_entry,
+        &ctx.accounts.player,
+        ctx.remaining_accounts,
+    )?;
+
+    lottery.open_entries = lottery
+        .open_entries
+        .checked_sub(1)
+        .ok_or(LotteryError::MathOverflow)?;
+
+    msg!(
+        "Closed entry of {} for lottery {}",
+        ctx.accounts.player.key(),
+        lottery.lottery_id
+    );
+
+    Ok(())
+}
+
+/// Closes each `Ticket` in `tickets` to `player`, who must hold its number
+/// through `player_entry`.
+pub(crate) fn close_pick_tickets<'info>(
+    lottery: &Lottery,
+    player_entry: &PlayerEntry,
+    player: &AccountInfo<'info>,
+    tickets: &'info [AccountInfo<'info>],
+) -> Result<()> {
+    for info in tickets {
+        let ticket = Account::<Ticket>::try_from(info)?;
+        require!(
+            ticket.lottery == player_entry.lottery && player_entry.owns(ticket.ticket_number),
+            LotteryError::TicketNotOwned
+        );
+        ticket.close(player.clone())?;
+    }
+
+    if !tickets.is_empty() {
+        msg!("Closed {} tickets of lottery {}", tickets.len(), lottery.lottery_id);
+    }
+
+    Ok(())
+}
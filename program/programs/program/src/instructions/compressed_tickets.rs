@@ -0,0 +1,134 @@
+use anchor_lang::prelude::*;
+use crate::accounting::ensure_pool_covered;
+use crate::compression::{ticket_leaf, TicketTree};
+use crate::contexts::{BuyCompressedTickets, InitTicketTree};
+use crate::errors::LotteryError;
+use crate::events::CompressedTicketsBought;
+use crate::state::{DrawMode, LotteryState};
+use crate::MAX_COMPRESSED_TICKETS_PER_PURCHASE;
+
+/// Switches a SOL lottery to compressed tickets before any ticket is sold,
+/// initializing `merkle_tree` with room for at least `max_tickets` leaves.
+pub fn init_ticket_tree_handler(
+    ctx: Context<InitTicketTree>,
+    max_depth: u32,
+    max_buffer_size: u32,
+) -> Result<()> {
+    let lottery = &mut ctx.accounts.lottery;
+
+    require!(lottery.state == LotteryState::Open, LotteryError::LotteryNotOpen);
+    require!(
+        lottery.tickets_sold == 0
+            && lottery.ticket_tree.is_none()
+            && lottery.mint.is_none()
+            && !lottery.ticket_nfts
+            && !matches!(lottery.draw_mode, DrawMode::Pick { .. }),
+        LotteryError::WrongTicketStorage
+    );
+    require!(
+        max_depth < 32 && (1u64 << max_depth) >= lottery.max_tickets as u64,
+        LotteryError::InvalidTicketTree
+    );
+
+    TicketTree {
+        compression_program: &ctx.accounts.compression_program,
+        merkle_tree: &ctx.accounts.merkle_tree,
+        noop_program: &ctx.accounts.noop_program,
+    }
+    .init(lottery, max_depth, max_buffer_size)?;
+    lottery.ticket_tree = Some(ctx.accounts.merkle_tree.key());
+
+    msg!(
+        "Lottery {} sells compressed tickets through tree {}",
+        lottery.lottery_id,
+        ctx.accounts.merkle_tree.key()
+    );
+
+    Ok(())
+}
+
+/// Buys `count` consecutive ticket numbers, appending one leaf per ticket to
+/// the lottery's ticket tree instead of recording them on a `PlayerEntry`.
+pub fn buy_compressed_tickets_handler(
+    ctx: Context<BuyCompressedTickets>,
+    lottery_id: u64,
+    count: u32,
+) -> Result<()> {
+    let lottery = &mut ctx.accounts.lottery;
+    let player = &ctx.accounts.player;
+
+    require!(lottery.lottery_id == lottery_id, LotteryError::InvalidLotteryId);
+    require!(!ctx.accounts.platform_config.paused, LotteryError::ProgramPaused);
+    require!(!lottery.paused, LotteryError::LotteryPaused);
+    require!(lottery.state != LotteryState::Cancelled, LotteryError::LotteryCancelled);
+    require!(lottery.state == LotteryState::Open, LotteryError::LotteryNotOpen);
+
+    let now = Clock::get()?.unix_timestamp;
+    if lottery.has_expired(now)? {
+        return err!(LotteryError::LotteryClosed);
+    }
+
+    require!(
+        count > 0 && count <= MAX_COMPRESSED_TICKETS_PER_PURCHASE,
+        LotteryError::InvalidTicketCount
+    );
+    let new_tickets_sold = lottery
+        .tickets_sold
+        .checked_add(count)
+        .ok_or(LotteryError::MathOverflow)?;
+    require!(new_tickets_sold <= lottery.max_tickets, LotteryError::LotteryFull);
+
+    let cost = lottery
+        .ticket_price
+        .checked_mul(count as u64)
+        .ok_or(LotteryError::MathOverflow)?;
+    let cpi_ctx = CpiContext::new(
+        ctx.accounts.system_program.to_account_info(),
+        anchor_lang::system_program::Transfer {
+            from: player.to_account_info(),
+            to: lottery.to_account_info(),
+        },
+    );
+    anchor_lang::system_program::transfer(cpi_ctx, cost)?;
+
+    // Leaf indices follow ticket numbers, as the tree only ever grows by appends
+    let tree = TicketTree {
+        compression_program: &ctx.accounts.compression_program,
+        merkle_tree: &ctx.accounts.merkle_tree,
+        noop_program: &ctx.accounts.noop_program,
+    };
+    let first_ticket = lottery.tickets_sold;
+    for ticket_number in first_ticket..new_tickets_sold {
+        tree.append(lottery, ticket_leaf(&lottery.key(), &player.key(), ticket_number, now))?;
+    }
+
+    lottery.tickets_sold = new_tickets_sold;
+    lottery.total_prize_pool = lottery
+        .total_prize_pool
+        .checked_add(cost)
+        .ok_or(LotteryError::MathOverflow)?;
+    ensure_pool_covered(lottery, None, lottery.total_prize_pool)?;
+
+    if lottery.is_sold_out() {
+        lottery.close_sales(&Clock::get()?)?;
+        msg!("All tickets sold! Lottery {} is now waiting for randomness.", lottery_id);
+    }
+
+    emit!(CompressedTicketsBought {
+        lottery: lottery.key(),
+        player: player.key(),
+        first_ticket,
+        count,
+        purchased_at: now,
+    });
+
+    msg!(
+        "Compressed tickets {}..{} purchased by {} for lottery {}",
+        first_ticket,
+        new_tickets_sold,
+        player.key(),
+        lottery_id
+    );
+
+    Ok(())
+}
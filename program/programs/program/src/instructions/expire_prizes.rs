@@ -0,0 +1,73 @@
+use anchor_lang::prelude::*;
+use crate::accounting::{ensure_pool_covered, pay_from_lottery, payee, settlement_pool, Treasury};
+use crate::contexts::ExpirePrizes;
+use crate::errors::LotteryError;
+use crate::state::{LotteryState, UnclaimedPrizes};
+
+/// Settles every prize tier still unclaimed after the claim period according
+/// to the lottery's `unclaimed_prizes` policy.
+pub fn expire_prizes_handler(ctx: Context<ExpirePrizes>) -> Result<()> {
+    let lottery = &mut ctx.accounts.lottery;
+
+    require!(lottery.state == LotteryState::WinnerDrawn, LotteryError::WinnerNotDrawn);
+    let now = Clock::get()?.unix_timestamp;
+    require!(now > lottery.prize_deadline()?, LotteryError::ClaimPeriodActive);
+
+    let unclaimed_tiers = lottery.unclaimed_tiers();
+    require!(!unclaimed_tiers.is_empty(), LotteryError::NoUnclaimedPrizes);
+
+    let treasury = Treasury::of(
+        lottery,
+        ctx.accounts.vault.as_ref(),
+        ctx.accounts.mint.as_ref(),
+        ctx.accounts.token_program.as_ref(),
+    )?;
+    let treasury = treasury.as_ref();
+    let pool = settlement_pool(lottery, treasury)?;
+    lottery.lock_payouts(pool)?;
+    ensure_pool_covered(lottery, treasury, lottery.total_prize_pool)?;
+
+    let mut unclaimed = 0u64;
+    for &tier in &unclaimed_tiers {
+        let amount = lottery.tier_remaining(tier)?;
+        unclaimed = unclaimed
+            .checked_add(amount)
+            .ok_or(LotteryError::MathOverflow)?;
+        lottery.record_claim(tier, amount, now)?;
+    }
+
+    match lottery.unclaimed_prizes {
+        UnclaimedPrizes::Rollover => {
+            lottery.rolled_over = lottery
+                .rolled_over
+                .checked_add(unclaimed)
+                .ok_or(LotteryError::MathOverflow)?;
+        }
+        UnclaimedPrizes::Creator => {
+            let to = payee(
+                treasury,
+                &ctx.accounts.lottery_creator,
+                ctx.accounts.creator_token_account.as_ref(),
+            )?;
+            pay_from_lottery(lottery, treasury, &to, unclaimed)?;
+        }
+        UnclaimedPrizes::Platform => {
+            let to = payee(
+                treasury,
+                &ctx.accounts.platform_fee_account,
+                ctx.accounts.platform_token_account.as_ref(),
+            )?;
+            pay_from_lottery(lottery, treasury, &to, unclaimed)?;
+        }
+    }
+
+    msg!(
+        "Expired {} unclaimed prize tiers of lottery {} worth {} ({:?})",
+        unclaimed_tiers.len(),
+        lottery.lottery_id,
+        unclaimed,
+        lottery.unclaimed_prizes
+    );
+
+    Ok(())
+}
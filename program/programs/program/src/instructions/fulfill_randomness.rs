@@ -1,6 +1,8 @@
 use anchor_lang::prelude::*;
 use crate::contexts::FulfillRandomness;
 use crate::errors::LotteryError;
+use crate::state::{LotteryState, RandomnessSource};
+use crate::ORACLE_AUTHORITY_SEED;
 
 pub fn fulfill_randomness_handler(
     ctx: Context<FulfillRandomness>,
@@ -8,21 +10,31 @@ pub fn fulfill_randomness_handler(
 ) -> Result<()> {
     let lottery = &mut ctx.accounts.lottery;
 
+    require!(lottery.state != LotteryState::Cancelled, LotteryError::LotteryCancelled);
+    require!(
+        lottery.state == LotteryState::AwaitingRandomness,
+        LotteryError::RandomnessNotRequested
+    );
     require!(!lottery.randomness_fulfilled, LotteryError::RandomnessAlreadyFulfilled);
-    require!(lottery.tickets_sold > 0, LotteryError::NoTicketsSold);
 
-    // Convert randomness to winning ticket number
-    let random_number = u64::from_le_bytes([
-        randomness[0], randomness[1], randomness[2], randomness[3],
-        randomness[4], randomness[5], randomness[6], randomness[7],
-    ]);
-    
-    let winning_ticket_number = (random_number % lottery.tickets_sold as u64) as u32;
+    let RandomnessSource::Oracle { program } = lottery.randomness_source else {
+        return err!(LotteryError::WrongRandomnessSource);
+    };
 
-    lottery.winner = Some(winning_ticket_number);
-    lottery.randomness_fulfilled = true;
+    // Only the oracle program itself can sign for its authority PDA
+    let (oracle_authority, _) = Pubkey::find_program_address(&[ORACLE_AUTHORITY_SEED], &program);
+    require_keys_eq!(
+        ctx.accounts.oracle_authority.key(),
+        oracle_authority,
+        LotteryError::UnauthorizedOracle
+    );
 
-    msg!("Randomness fulfilled. Winning ticket number: {}", winning_ticket_number);
+    let clock = Clock::get()?;
+    require!(clock.slot <= lottery.oracle_deadline(), LotteryError::OracleTimeout);
+
+    lottery.draw_winners(randomness, clock.unix_timestamp)?;
+
+    msg!("Randomness fulfilled by oracle {}. Winning tickets by tier: {:?}", program, lottery.winners);
 
     Ok(())
 }
@@ -1,5 +1,11 @@
 use anchor_lang::prelude::*;
+use crate::accounting::validate_mint;
 use crate::contexts::InitializeLottery;
+use crate::errors::LotteryError;
+use crate::state::{
+    DrawMode, Lottery, LotteryState, LotteryTerms, PlatformConfig, PrizeSplit, RandomnessSource,
+    UnclaimedPrizes,
+};
 
 pub fn initialize_lottery_handler(
     ctx: Context<InitializeLottery>,
@@ -7,21 +13,41 @@ pub fn initialize_lottery_handler(
     ticket_price: u64,
     max_tickets: u32,
     duration: u64, // ⏳ new param
+    prize_split: PrizeSplit,
+    randomness_source: RandomnessSource,
+    ticket_nfts: bool,
 ) -> Result<()> {
     let lottery = &mut ctx.accounts.lottery;
-    let clock = Clock::get()?;
+    let terms = LotteryTerms {
+        ticket_price,
+        max_tickets,
+        duration,
+        prize_split,
+        draw_mode: DrawMode::Standard,
+    };
+
+    if let Some(mint) = &ctx.accounts.mint {
+        require!(ctx.accounts.vault.is_some(), LotteryError::MissingTokenAccounts);
+        validate_mint(&mint.to_account_info())?;
+    }
+    open_lottery(lottery, &ctx.accounts.platform_config, terms, randomness_source)?;
 
     lottery.authority = ctx.accounts.authority.key();
     lottery.lottery_id = lottery_id;
-    lottery.ticket_price = ticket_price;
-    lottery.max_tickets = max_tickets;
-    lottery.tickets_sold = 0;
-    lottery.total_prize_pool = 0;
-    lottery.winner = None;
-    lottery.created_at = clock.unix_timestamp;
-    lottery.duration = duration; 
-    lottery.randomness_fulfilled = false;
+    lottery.series = None;
+    lottery.mint = ctx.accounts.mint.as_ref().map(|mint| mint.key());
+    lottery.ticket_registry = ctx.accounts.ticket_registry.key();
     lottery.bump = ctx.bumps.lottery;
+    lottery.ticket_nfts = ticket_nfts;
+    // There is no next round to roll unclaimed prizes into
+    if lottery.unclaimed_prizes == UnclaimedPrizes::Rollover {
+        lottery.unclaimed_prizes = UnclaimedPrizes::Creator;
+    }
+
+    let mut ticket_registry = ctx.accounts.ticket_registry.load_init()?;
+    ticket_registry.lottery = lottery.key();
+    ticket_registry.ticket_count = 0;
+    ticket_registry.player_count = 0;
 
     msg!(
         "Lottery initialized with ID: {}, ticket price: {}, max tickets: {}, duration: {} sec",
@@ -33,3 +59,83 @@ pub fn initialize_lottery_handler(
 
     Ok(())
 }
+
+/// Checks `terms` and the randomness source against the platform limits and
+/// resets every field of `lottery` except the ones identifying it: authority,
+/// id, series, mint, ticket registry and bump.
+pub(crate) fn open_lottery(
+    lottery: &mut Lottery,
+    platform_config: &PlatformConfig,
+    terms: LotteryTerms,
+    randomness_source: RandomnessSource,
+) -> Result<()> {
+    require!(!platform_config.paused, LotteryError::ProgramPaused);
+    validate_terms(platform_config, &terms)?;
+    if let RandomnessSource::Oracle { program } = randomness_source {
+        require!(
+            platform_config.oracle_programs.contains(&program),
+            LotteryError::OracleNotApproved
+        );
+    }
+
+    lottery.ticket_price = terms.ticket_price;
+    lottery.max_tickets = terms.max_tickets;
+    lottery.tickets_sold = 0;
+    lottery.open_entries = 0;
+    lottery.total_prize_pool = 0;
+    lottery.carried_over = 0;
+    lottery.jackpot = 0;
+    lottery.state = LotteryState::Open;
+    lottery.paused = false;
+    lottery.transfers_locked_at_close = false;
+    lottery.ticket_nfts = false;
+    lottery.ticket_tree = None;
+    lottery.draw_mode = terms.draw_mode;
+    lottery.winners = Vec::new();
+    lottery.winning_numbers = Vec::new();
+    lottery.tier_winners = Vec::new();
+    lottery.tier_payments = Vec::new();
+    lottery.created_at = Clock::get()?.unix_timestamp;
+    lottery.duration = terms.duration;
+    lottery.prize_split = terms.prize_split;
+    lottery.randomness_fulfilled = false;
+    lottery.randomness = [0; 32];
+    lottery.randomness_source = randomness_source;
+    lottery.reveal_slot = 0;
+    lottery.reveal_slot_hash = None;
+    lottery.reveal_deadline = 0;
+    lottery.randomness_request_slot = 0;
+    lottery.paid_out_at = None;
+    lottery.fees_paid = false;
+    lottery.payouts_locked = false;
+    lottery.claimed_tiers = 0;
+    lottery.claim_period = platform_config.claim_period;
+    lottery.unclaimed_prizes = platform_config.unclaimed_prizes;
+    lottery.claim_deadline = 0;
+    lottery.crank_reward_bps = platform_config.crank_reward_bps;
+    lottery.sales_closer = None;
+    lottery.draw_requester = None;
+    lottery.rolled_over = 0;
+    lottery.next_round = None;
+    lottery.winner_payout = 0;
+    lottery.creator_payout = 0;
+    lottery.platform_payout = 0;
+    lottery.crank_payout = 0;
+
+    Ok(())
+}
+
+/// Checks `terms` against the platform's price, duration and fee limits.
+pub(crate) fn validate_terms(platform_config: &PlatformConfig, terms: &LotteryTerms) -> Result<()> {
+    require!(
+        (platform_config.min_ticket_price..=platform_config.max_ticket_price)
+            .contains(&terms.ticket_price),
+        LotteryError::TicketPriceOutOfRange
+    );
+    require!(
+        (platform_config.min_duration..=platform_config.max_duration).contains(&terms.duration),
+        LotteryError::DurationOutOfRange
+    );
+    terms.prize_split.validate(platform_config.min_fee_bps)?;
+    terms.draw_mode.validate(terms.prize_split.tier_bps.len())
+}
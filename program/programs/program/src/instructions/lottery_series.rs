@@ -0,0 +1,171 @@
+use anchor_lang::prelude::*;
+use crate::accounting::{
+    ensure_pool_covered, pay_from_jackpot, pay_from_lottery, transfer_lamports, validate_mint,
+    vault_balance, Treasury,
+};
+use crate::contexts::{InitializeSeries, OpenRound};
+use crate::errors::LotteryError;
+use crate::instructions::{open_lottery, validate_terms};
+use crate::state::{LotteryTerms, RandomnessSource};
+
+pub fn initialize_series_handler(
+    ctx: Context<InitializeSeries>,
+    series_id: u64,
+    terms: LotteryTerms,
+) -> Result<()> {
+    let series = &mut ctx.accounts.series;
+
+    // Rounds are checked again as they open, in case the limits change
+    validate_terms(&ctx.accounts.platform_config, &terms)?;
+    if let Some(mint) = &ctx.accounts.mint {
+        require!(ctx.accounts.jackpot_vault.is_some(), LotteryError::MissingTokenAccounts);
+        validate_mint(&mint.to_account_info())?;
+    }
+
+    series.authority = ctx.accounts.authority.key();
+    series.series_id = series_id;
+    series.mint = ctx.accounts.mint.as_ref().map(|mint| mint.key());
+    series.terms = terms;
+    series.rounds = 0;
+    series.current_round = Pubkey::default();
+    series.bump = ctx.bumps.series;
+
+    let jackpot = &mut ctx.accounts.jackpot;
+    jackpot.series = series.key();
+    jackpot.amount = 0;
+    jackpot.bump = ctx.bumps.jackpot;
+
+    msg!(
+        "Series {} initialized with ticket price: {}, max tickets: {}, duration: {} sec",
+        series_id,
+        series.terms.ticket_price,
+        series.terms.max_tickets,
+        series.terms.duration
+    );
+
+    Ok(())
+}
+
+/// Opens the series' next round once the previous one has settled, carrying
+/// over whatever the previous round rolled over along with the jackpot. A
+/// cancelled round hands over right away and keeps its unclaimed refunds.
+pub fn open_round_handler(
+    ctx: Context<OpenRound>,
+    randomness_source: RandomnessSource,
+) -> Result<()> {
+    let series = &mut ctx.accounts.series;
+    let lottery = &mut ctx.accounts.lottery;
+
+    require!(
+        ctx.accounts.mint.as_ref().map(|mint| mint.key()) == series.mint,
+        LotteryError::InvalidMint
+    );
+    require!(
+        ctx.accounts.vault.is_some() == series.mint.is_some(),
+        LotteryError::MissingTokenAccounts
+    );
+
+    open_lottery(
+        lottery,
+        &ctx.accounts.platform_config,
+        series.terms.clone(),
+        randomness_source,
+    )?;
+
+    let round = series.rounds;
+    lottery.authority = series.authority;
+    lottery.lottery_id = round;
+    lottery.series = Some(series.key());
+    lottery.mint = series.mint;
+    lottery.ticket_registry = ctx.accounts.ticket_registry.key();
+    lottery.bump = ctx.bumps.lottery;
+
+    let mut ticket_registry = ctx.accounts.ticket_registry.load_init()?;
+    ticket_registry.lottery = lottery.key();
+    ticket_registry.ticket_count = 0;
+    ticket_registry.player_count = 0;
+
+    if round > 0 {
+        let previous = ctx
+            .accounts
+            .previous_round
+            .as_mut()
+            .ok_or(LotteryError::InvalidPreviousRound)?;
+        require!(previous.can_hand_over(), LotteryError::PreviousRoundNotSettled);
+
+        let carried = previous.rolled_over;
+        if carried > 0 {
+            let treasury = Treasury::of(
+                previous,
+                ctx.accounts.previous_vault.as_ref(),
+                ctx.accounts.mint.as_ref(),
+                ctx.accounts.token_program.as_ref(),
+            )?;
+            // Refunds still owed stay behind in the previous round
+            let reserved = previous
+                .total_prize_pool
+                .checked_add(carried)
+                .ok_or(LotteryError::MathOverflow)?;
+            ensure_pool_covered(previous, treasury.as_ref(), reserved)?;
+            match (treasury, &ctx.accounts.vault) {
+                (None, _) => {
+                    transfer_lamports(&previous.to_account_info(), &lottery.to_account_info(), carried)?;
+                    lottery.carried_over = carried;
+                }
+                (Some(treasury), Some(vault)) => {
+                    let vault_info = vault.to_account_info();
+                    let before = vault_balance(&vault_info)?;
+                    pay_from_lottery(previous, Some(&treasury), &vault_info, carried)?;
+                    // A transfer fee may have been withheld on the way
+                    lottery.carried_over = vault_balance(&vault_info)?
+                        .checked_sub(before)
+                        .ok_or(LotteryError::MathOverflow)?;
+                }
+                (Some(_), None) => return err!(LotteryError::MissingTokenAccounts),
+            }
+        }
+
+        previous.rolled_over = 0;
+        previous.next_round = Some(lottery.key());
+    }
+
+    let jackpot = &mut ctx.accounts.jackpot;
+    if jackpot.amount > 0 {
+        let treasury = Treasury::for_mint(
+            series.mint,
+            ctx.accounts.jackpot_vault.as_ref(),
+            ctx.accounts.mint.as_ref(),
+            ctx.accounts.token_program.as_ref(),
+        )?;
+        match (treasury, &ctx.accounts.vault) {
+            (None, _) => {
+                pay_from_jackpot(jackpot, None, &lottery.to_account_info(), jackpot.amount)?;
+                lottery.jackpot = jackpot.amount;
+            }
+            (Some(treasury), Some(vault)) => {
+                let vault_info = vault.to_account_info();
+                let before = vault_balance(&vault_info)?;
+                pay_from_jackpot(jackpot, Some(&treasury), &vault_info, jackpot.amount)?;
+                lottery.jackpot = vault_balance(&vault_info)?
+                    .checked_sub(before)
+                    .ok_or(LotteryError::MathOverflow)?;
+            }
+            (Some(_), None) => return err!(LotteryError::MissingTokenAccounts),
+        }
+        jackpot.amount = 0;
+    }
+    lottery.total_prize_pool = lottery.inherited()?;
+
+    series.rounds = round.checked_add(1).ok_or(LotteryError::MathOverflow)?;
+    series.current_round = lottery.key();
+
+    msg!(
+        "Opened round {} of series {} with {} carried over and a jackpot of {}",
+        round,
+        series.series_id,
+        lottery.carried_over,
+        lottery.jackpot
+    );
+
+    Ok(())
+}
@@ -1,9 +1,41 @@
+pub mod platform_config;
+pub mod set_lottery_paused;
 pub mod initialize_lottery;
+pub mod lottery_series;
 pub mod buy_ticket;
+pub mod transfer_ticket;
+pub mod compressed_tickets;
+pub mod close_sales;
+pub mod reveal_randomness;
+pub mod request_randomness;
 pub mod fulfill_randomness;
 pub mod payout;
+pub mod claim_prize;
+pub mod claim_numbers;
+pub mod claim_compressed;
+pub mod expire_prizes;
+pub mod cancel_lottery;
+pub mod claim_refund;
+pub mod close_ticket;
+pub mod close_lottery;
 
+pub use platform_config::*;
+pub use set_lottery_paused::*;
 pub use initialize_lottery::*;
+pub use lottery_series::*;
 pub use buy_ticket::*;
+pub use transfer_ticket::*;
+pub use compressed_tickets::*;
+pub use close_sales::*;
+pub use reveal_randomness::*;
+pub use request_randomness::*;
 pub use fulfill_randomness::*;
 pub use payout::*;
+pub use claim_prize::*;
+pub use claim_numbers::*;
+pub use claim_compressed::*;
+pub use expire_prizes::*;
+pub use cancel_lottery::*;
+pub use claim_refund::*;
+pub use close_ticket::*;
+pub use close_lottery::*;
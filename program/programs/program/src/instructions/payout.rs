@@ -1,75 +1,207 @@
 use anchor_lang::prelude::*;
+use anchor_spl::token_interface::TokenAccount;
+use crate::accounting::{
+    ensure_pool_covered, pay_from_lottery, payee, settlement_pool, transfer_lamports,
+    vault_balance, Treasury,
+};
 use crate::contexts::Payout;
 use crate::errors::LotteryError;
+use crate::state::{DrawMode, Jackpot, Lottery, LotteryState};
+use crate::CRANK_STEPS;
 
+/// Pays the creator and platform shares, and the crank reward to the keepers
+/// who closed sales, requested the draw and settle the lottery. Winners
+/// collect their prize tiers through `claim_prize`.
 pub fn payout_handler(ctx: Context<Payout>) -> Result<()> {
     let lottery = &mut ctx.accounts.lottery;
-    let winner_ticket = &ctx.accounts.winner_ticket;
 
+    require!(!lottery.fees_paid, LotteryError::AlreadyPaidOut);
+    require!(lottery.state == LotteryState::WinnerDrawn, LotteryError::WinnerNotDrawn);
+    require!(lottery.randomness_fulfilled, LotteryError::RandomnessNotFulfilled);
+    require!(lottery.drawn_tiers() > 0, LotteryError::NoWinner);
 
-    require!(lottery.winner.is_some(), LotteryError::NoWinner);
+    let now = Clock::get()?.unix_timestamp;
     require!(
-        winner_ticket.ticket_number == lottery.winner.unwrap(),
-        LotteryError::InvalidWinnerTicket
+        lottery.has_expired(now)? || lottery.is_sold_out(),
+        LotteryError::SalesStillOpen
     );
+    // Pick tiers are only known to be unwon once registration has closed
+    if let DrawMode::Pick { .. } = lottery.draw_mode {
+        require!(now > lottery.claim_deadline, LotteryError::ClaimPeriodActive);
+    }
 
-    let total_prize_pool = lottery.total_prize_pool;
+    // Only lamports above the rent-exempt minimum, or the vault's tokens,
+    // belong to the pool. A token lottery splits its whole vault balance.
+    let treasury = Treasury::of(
+        lottery,
+        ctx.accounts.vault.as_ref(),
+        ctx.accounts.mint.as_ref(),
+        ctx.accounts.token_program.as_ref(),
+    )?;
+    let treasury = treasury.as_ref();
+    let pool = settlement_pool(lottery, treasury)?;
+    lottery.lock_payouts(pool)?;
+    ensure_pool_covered(lottery, treasury, lottery.total_prize_pool)?;
 
-    // Calculate distribution amounts with strict policy
-    let winner_amount = (total_prize_pool * 90) / 100; // 90% to winner
-    let creator_amount = (total_prize_pool * 5) / 100; // 5% to lottery creator
-    let platform_amount =
-        total_prize_pool - winner_amount - creator_amount; // Remaining 5% to platform
+    let creator_amount = lottery.creator_payout;
+    let platform_amount = lottery.platform_payout;
+    let crank_amount = lottery.crank_payout;
 
-    // Ensure we don't have any rounding issues
-    require!(
-        winner_amount + creator_amount + platform_amount == total_prize_pool,
-        LotteryError::InvalidPayout
-    );
+    // Transfer creator share
+    let creator = payee(
+        treasury,
+        &ctx.accounts.lottery_creator,
+        ctx.accounts.creator_token_account.as_ref(),
+    )?;
+    pay_from_lottery(lottery, treasury, &creator, creator_amount)?;
 
-    // Transfer 90% to winner
-    **lottery.to_account_info().try_borrow_mut_lamports()? -= winner_amount;
-    **ctx
-        .accounts
-        .winner
-        .to_account_info()
-        .try_borrow_mut_lamports()? += winner_amount;
-
-    // Transfer 5% to lottery creator
-    **lottery.to_account_info().try_borrow_mut_lamports()? -= creator_amount;
-    **ctx
-        .accounts
-        .lottery_creator
-        .to_account_info()
-        .try_borrow_mut_lamports()? += creator_amount;
-
-    // Transfer 5% to platform fee account
-    **lottery.to_account_info().try_borrow_mut_lamports()? -= platform_amount;
-    **ctx
-        .accounts
-        .platform_fee_account
-        .to_account_info()
-        .try_borrow_mut_lamports()? += platform_amount;
-
-    // Update lottery state
-    lottery.total_prize_pool = 0;
-
-    msg!("Payout completed with strict 90/5/5 distribution:");
-    msg!(
-        "Winner {} received {} lamports (90%)",
-        ctx.accounts.winner.key(),
-        winner_amount
-    );
+    // Transfer platform share
+    let platform = payee(
+        treasury,
+        &ctx.accounts.platform_fee_account,
+        ctx.accounts.platform_token_account.as_ref(),
+    )?;
+    pay_from_lottery(lottery, treasury, &platform, platform_amount)?;
+
+    // Pay the crank reward. Nothing leaves the pool for keepers before the
+    // draw, so a cancelled lottery can still refund every ticket in full.
+    // Each recorded keeper gets its step's share and the caller the rest.
+    let keeper_share = crank_amount / CRANK_STEPS;
+    let keepers = [
+        (
+            lottery.sales_closer,
+            ctx.accounts.sales_closer.as_ref(),
+            ctx.accounts.sales_closer_token_account.as_deref(),
+        ),
+        (
+            lottery.draw_requester,
+            ctx.accounts.draw_requester.as_ref(),
+            ctx.accounts.draw_requester_token_account.as_deref(),
+        ),
+    ];
+    let mut caller_amount = crank_amount;
+    for (recorded, keeper, keeper_token_account) in keepers {
+        if recorded.is_none() || keeper_share == 0 {
+            continue;
+        }
+        let keeper = keeper.ok_or(LotteryError::MissingKeeper)?.to_account_info();
+        // A wallet emptied since its step could not hold a small share
+        // rent-exempt; its share goes to the caller rather than block payout
+        if treasury.is_none() {
+            let balance = keeper
+                .lamports()
+                .checked_add(keeper_share)
+                .ok_or(LotteryError::MathOverflow)?;
+            if !Rent::get()?.is_exempt(balance, keeper.data_len()) {
+                continue;
+            }
+        }
+        let to = payee(treasury, &keeper, keeper_token_account)?;
+        pay_from_lottery(lottery, treasury, &to, keeper_share)?;
+        caller_amount = caller_amount
+            .checked_sub(keeper_share)
+            .ok_or(LotteryError::MathOverflow)?;
+        msg!("Keeper {} received a crank share of {}", keeper.key(), keeper_share);
+    }
+    if caller_amount > 0 {
+        let caller = payee(
+            treasury,
+            &ctx.accounts.caller.to_account_info(),
+            ctx.accounts.caller_token_account.as_ref(),
+        )?;
+        pay_from_lottery(lottery, treasury, &caller, caller_amount)?;
+    }
+
+    // Prizes nobody won grow the series' jackpot
+    let unwon = sweep_unwon_prizes(
+        lottery,
+        treasury,
+        ctx.accounts.jackpot.as_mut(),
+        ctx.accounts.jackpot_vault.as_ref(),
+        now,
+    )?;
+
+    let fees = creator_amount
+        .checked_add(platform_amount)
+        .and_then(|fees| fees.checked_add(crank_amount))
+        .ok_or(LotteryError::MathOverflow)?;
+    lottery.fees_paid = true;
+    lottery.record_payment(fees, now)?;
+
+    msg!("Payout completed:");
     msg!(
-        "Creator {} received {} lamports (5%)",
+        "Creator {} received {} lamports ({} bps)",
         ctx.accounts.lottery_creator.key(),
-        creator_amount
+        creator_amount,
+        lottery.prize_split.creator_bps
     );
     msg!(
-        "Platform {} received {} lamports (5%)",
+        "Platform {} received {} lamports ({} bps)",
         ctx.accounts.platform_fee_account.key(),
-        platform_amount
+        platform_amount,
+        lottery.prize_split.platform_bps
+    );
+    msg!(
+        "Caller {} received {} of the {} lamport crank reward",
+        ctx.accounts.caller.key(),
+        caller_amount,
+        crank_amount
+    );
+    msg!(
+        "{} lamports ({} bps) left for {} prize tiers, {} moved to the jackpot",
+        lottery.winner_payout,
+        lottery.prize_split.winner_bps,
+        lottery.drawn_tiers(),
+        unwon
     );
 
     Ok(())
 }
+
+/// Moves the prizes of every tier without a winner into the series'
+/// jackpot and marks those tiers as settled. Returns the amount moved.
+fn sweep_unwon_prizes<'info>(
+    lottery: &mut Account<'info, Lottery>,
+    treasury: Option<&Treasury<'_, 'info>>,
+    jackpot: Option<&mut Account<'info, Jackpot>>,
+    jackpot_vault: Option<&InterfaceAccount<'info, TokenAccount>>,
+    now: i64,
+) -> Result<u64> {
+    let unwon_tiers = lottery.unwon_tiers();
+    if unwon_tiers.is_empty() {
+        return Ok(0);
+    }
+    let jackpot = jackpot.ok_or(LotteryError::InvalidJackpot)?;
+
+    let mut unwon = 0u64;
+    for &tier in &unwon_tiers {
+        let amount = lottery.tier_payout(tier)?;
+        unwon = unwon
+            .checked_add(amount)
+            .ok_or(LotteryError::MathOverflow)?;
+        lottery.record_claim(tier, amount, now)?;
+    }
+
+    let received = match treasury {
+        None => {
+            transfer_lamports(&lottery.to_account_info(), &jackpot.to_account_info(), unwon)?;
+            unwon
+        }
+        Some(treasury) => {
+            let jackpot_vault = jackpot_vault.ok_or(LotteryError::MissingTokenAccounts)?;
+            let vault_info = jackpot_vault.to_account_info();
+            let before = vault_balance(&vault_info)?;
+            pay_from_lottery(lottery, Some(treasury), &vault_info, unwon)?;
+            // A transfer fee may have been withheld on the way
+            vault_balance(&vault_info)?
+                .checked_sub(before)
+                .ok_or(LotteryError::MathOverflow)?
+        }
+    };
+    jackpot.amount = jackpot
+        .amount
+        .checked_add(received)
+        .ok_or(LotteryError::MathOverflow)?;
+
+    Ok(unwon)
+}
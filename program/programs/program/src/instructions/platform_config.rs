@@ -0,0 +1,81 @@
+use anchor_lang::prelude::*;
+use crate::contexts::{AcceptPlatformAdmin, InitializePlatform, UpdatePlatformConfig};
+use crate::events::PlatformPauseChanged;
+use crate::state::PlatformSettings;
+
+pub fn initialize_platform_handler(
+    ctx: Context<InitializePlatform>,
+    settings: PlatformSettings,
+) -> Result<()> {
+    let platform_config = &mut ctx.accounts.platform_config;
+
+    platform_config.admin = ctx.accounts.admin.key();
+    platform_config.pending_admin = None;
+    platform_config.apply(&settings)?;
+    platform_config.paused = false;
+    platform_config.bump = ctx.bumps.platform_config;
+
+    msg!(
+        "Platform initialized. Admin: {}, fee wallet: {}",
+        platform_config.admin,
+        platform_config.fee_wallet
+    );
+
+    Ok(())
+}
+
+pub fn update_platform_config_handler(
+    ctx: Context<UpdatePlatformConfig>,
+    settings: PlatformSettings,
+) -> Result<()> {
+    let platform_config = &mut ctx.accounts.platform_config;
+
+    platform_config.apply(&settings)?;
+
+    msg!("Platform config updated. Fee wallet: {}", platform_config.fee_wallet);
+
+    Ok(())
+}
+
+/// Nominates `new_admin`, who only takes over by signing
+/// `accept_platform_admin`, so a mistyped key cannot lock the platform.
+/// Nominating again replaces any pending admin.
+pub fn transfer_platform_admin_handler(
+    ctx: Context<UpdatePlatformConfig>,
+    new_admin: Pubkey,
+) -> Result<()> {
+    let platform_config = &mut ctx.accounts.platform_config;
+
+    platform_config.pending_admin = Some(new_admin);
+
+    msg!("Platform admin {} nominated {} to take over", platform_config.admin, new_admin);
+
+    Ok(())
+}
+
+pub fn accept_platform_admin_handler(ctx: Context<AcceptPlatformAdmin>) -> Result<()> {
+    let platform_config = &mut ctx.accounts.platform_config;
+    let new_admin = ctx.accounts.new_admin.key();
+
+    msg!("Platform admin transferred from {} to {}", platform_config.admin, new_admin);
+
+    platform_config.admin = new_admin;
+    platform_config.pending_admin = None;
+
+    Ok(())
+}
+
+pub fn set_platform_paused_handler(ctx: Context<UpdatePlatformConfig>, paused: bool) -> Result<()> {
+    let platform_config = &mut ctx.accounts.platform_config;
+
+    platform_config.paused = paused;
+
+    emit!(PlatformPauseChanged {
+        admin: ctx.accounts.admin.key(),
+        paused,
+    });
+
+    msg!("Platform paused: {}", paused);
+
+    Ok(())
+}
@@ -0,0 +1,29 @@
+use anchor_lang::prelude::*;
+use crate::contexts::RequestRandomness;
+use crate::errors::LotteryError;
+use crate::state::{LotteryState, RandomnessSource};
+
+pub fn request_randomness_handler(ctx: Context<RequestRandomness>) -> Result<()> {
+    let lottery = &mut ctx.accounts.lottery;
+
+    require!(lottery.state != LotteryState::Cancelled, LotteryError::LotteryCancelled);
+    require!(lottery.state == LotteryState::SalesClosed, LotteryError::SalesNotClosed);
+    require!(lottery.tickets_sold > 0, LotteryError::NoTicketsSold);
+
+    let RandomnessSource::Oracle { program } = lottery.randomness_source else {
+        return err!(LotteryError::WrongRandomnessSource);
+    };
+
+    lottery.state = LotteryState::AwaitingRandomness;
+    lottery.randomness_request_slot = Clock::get()?.slot;
+    lottery.draw_requester = Some(ctx.accounts.caller.key());
+
+    msg!(
+        "Randomness requested for lottery {} from oracle {} at slot {}",
+        lottery.lottery_id,
+        program,
+        lottery.randomness_request_slot
+    );
+
+    Ok(())
+}
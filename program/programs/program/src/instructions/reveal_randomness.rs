@@ -0,0 +1,98 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::hash::{hash, hashv};
+use crate::contexts::{RecordSlotHash, RevealRandomness};
+use crate::errors::LotteryError;
+use crate::state::{Lottery, LotteryState, RandomnessSource};
+
+pub fn record_slot_hash_handler(ctx: Context<RecordSlotHash>) -> Result<()> {
+    let lottery = &mut ctx.accounts.lottery;
+
+    require!(lottery.state != LotteryState::Cancelled, LotteryError::LotteryCancelled);
+    require!(lottery.state == LotteryState::SalesClosed, LotteryError::SalesNotClosed);
+    require!(
+        matches!(lottery.randomness_source, RandomnessSource::CommitReveal { .. }),
+        LotteryError::WrongRandomnessSource
+    );
+    require!(lottery.reveal_slot_hash.is_none(), LotteryError::SlotHashAlreadyRecorded);
+
+    let slot_hash = record_reveal_slot_hash(lottery, &ctx.accounts.slot_hashes)?;
+    lottery.draw_requester = Some(ctx.accounts.caller.key());
+
+    msg!(
+        "Hash of slot {} recorded for lottery {}: {:?}",
+        lottery.reveal_slot,
+        lottery.lottery_id,
+        slot_hash
+    );
+
+    Ok(())
+}
+
+pub fn reveal_randomness_handler(
+    ctx: Context<RevealRandomness>,
+    secret: [u8; 32],
+) -> Result<()> {
+    let lottery = &mut ctx.accounts.lottery;
+
+    require!(lottery.state != LotteryState::Cancelled, LotteryError::LotteryCancelled);
+    require!(lottery.state == LotteryState::SalesClosed, LotteryError::SalesNotClosed);
+    require!(!lottery.randomness_fulfilled, LotteryError::RandomnessAlreadyFulfilled);
+    require!(lottery.tickets_sold > 0, LotteryError::NoTicketsSold);
+
+    let RandomnessSource::CommitReveal { commitment } = lottery.randomness_source else {
+        return err!(LotteryError::WrongRandomnessSource);
+    };
+    require!(hash(&secret).to_bytes() == commitment, LotteryError::InvalidReveal);
+
+    let now = Clock::get()?.unix_timestamp;
+    require!(now <= lottery.reveal_deadline, LotteryError::RevealWindowExpired);
+
+    // Revealing soon after sales close can still read the hash from the sysvar
+    let slot_hash = match lottery.reveal_slot_hash {
+        Some(slot_hash) => slot_hash,
+        None => record_reveal_slot_hash(lottery, &ctx.accounts.slot_hashes)?,
+    };
+
+    // Neither the authority (who knew the secret) nor the validator (who
+    // produced the slot hash) controls both inputs
+    let randomness = hashv(&[&secret, &slot_hash, lottery.key().as_ref()]).to_bytes();
+
+    lottery.draw_winners(randomness, now)?;
+
+    msg!("Randomness revealed. Winning tickets by tier: {:?}", lottery.winners);
+
+    Ok(())
+}
+
+/// Copies the hash of the lottery's reveal slot from SlotHashes onto the
+/// lottery, so the secret can still be revealed once the sysvar has moved on.
+fn record_reveal_slot_hash(lottery: &mut Lottery, slot_hashes: &AccountInfo) -> Result<[u8; 32]> {
+    // The reveal slot's hash only exists once that slot has been produced
+    require!(Clock::get()?.slot > lottery.reveal_slot, LotteryError::RevealTooEarly);
+
+    let slot_hash = find_slot_hash(slot_hashes, lottery.reveal_slot)?;
+    lottery.reveal_slot_hash = Some(slot_hash);
+    Ok(slot_hash)
+}
+
+/// Looks up the hash of `slot` in the raw SlotHashes sysvar data, laid out as
+/// a u64 entry count followed by (u64 slot, [u8; 32] hash) entries.
+fn find_slot_hash(slot_hashes: &AccountInfo, slot: u64) -> Result<[u8; 32]> {
+    const ENTRY_LEN: usize = 8 + 32;
+
+    let data = slot_hashes.try_borrow_data()?;
+    let len_bytes: [u8; 8] = data
+        .get(..8)
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or(LotteryError::SlotHashNotFound)?;
+    let len = u64::from_le_bytes(len_bytes) as usize;
+
+    for entry in data[8..].chunks_exact(ENTRY_LEN).take(len) {
+        let entry_slot = u64::from_le_bytes(entry[..8].try_into().unwrap());
+        if entry_slot == slot {
+            return Ok(entry[8..].try_into().unwrap());
+        }
+    }
+
+    err!(LotteryError::SlotHashNotFound)
+}
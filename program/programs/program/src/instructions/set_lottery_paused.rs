@@ -0,0 +1,19 @@
+use anchor_lang::prelude::*;
+use crate::contexts::SetLotteryPaused;
+use crate::events::LotteryPauseChanged;
+
+pub fn set_lottery_paused_handler(ctx: Context<SetLotteryPaused>, paused: bool) -> Result<()> {
+    let lottery = &mut ctx.accounts.lottery;
+
+    lottery.paused = paused;
+
+    emit!(LotteryPauseChanged {
+        admin: ctx.accounts.admin.key(),
+        lottery: lottery.key(),
+        paused,
+    });
+
+    msg!("Lottery {} paused: {}", lottery.lottery_id, paused);
+
+    Ok(())
+}
@@ -0,0 +1,126 @@
+use anchor_lang::prelude::*;
+use crate::contexts::{SetTransferLock, TransferTicket};
+use crate::errors::LotteryError;
+use crate::events::{TicketTransferred, TransferLockChanged};
+use crate::instructions::{grow_account, open_entry};
+use crate::state::{LotteryState, PlayerEntry, TicketRegistry};
+
+/// Hands ticket `ticket_number` over to `recipient` along with its share of
+/// what the holder spent, so a refund follows the ticket too.
+pub fn transfer_ticket_handler(
+    ctx: Context<TransferTicket>,
+    ticket_number: u32,
+    recipient: Pubkey,
+) -> Result<()> {
+    let lottery = &mut ctx.accounts.lottery;
+    let holder = &ctx.accounts.holder;
+    let holder_entry = &mut ctx.accounts.holder_entry;
+    let recipient_entry = &mut ctx.accounts.recipient_entry;
+
+    require!(!ctx.accounts.platform_config.paused, LotteryError::ProgramPaused);
+    require!(!lottery.paused, LotteryError::LotteryPaused);
+    require!(!lottery.ticket_nfts, LotteryError::TicketsAreNfts);
+    require!(lottery.accepts_transfers(), LotteryError::TransfersClosed);
+    require_keys_neq!(recipient, holder.key(), LotteryError::SelfTransfer);
+
+    let registry_info = ctx.accounts.ticket_registry.to_account_info();
+    let owner = TicketRegistry::owner_of(&registry_info.try_borrow_data()?, ticket_number);
+    require!(
+        owner == Some(holder_entry.index) && holder_entry.owns(ticket_number),
+        LotteryError::TicketNotOwned
+    );
+
+    // The last ticket takes whatever is left, so nothing stays behind
+    let spent = if holder_entry.ticket_count == 1 {
+        holder_entry.total_spent
+    } else {
+        holder_entry.total_spent / holder_entry.ticket_count as u64
+    };
+
+    if holder_entry.splits_at(ticket_number) {
+        grow_account(
+            &holder_entry.to_account_info(),
+            &holder.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            PlayerEntry::space(holder_entry.ranges.len() + 1),
+        )?;
+    }
+    holder_entry.release(ticket_number)?;
+    holder_entry.total_spent = holder_entry
+        .total_spent
+        .checked_sub(spent)
+        .ok_or(LotteryError::MathOverflow)?;
+
+    {
+        let mut ticket_registry = ctx.accounts.ticket_registry.load_mut()?;
+        open_entry(
+            recipient_entry,
+            lottery,
+            &mut ticket_registry,
+            recipient,
+            ctx.bumps.recipient_entry,
+        )?;
+    }
+    TicketRegistry::assign(
+        &mut registry_info.try_borrow_mut_data()?,
+        ticket_number,
+        1,
+        recipient_entry.index,
+    );
+
+    if recipient_entry.needs_range_for(ticket_number) {
+        grow_account(
+            &recipient_entry.to_account_info(),
+            &holder.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            PlayerEntry::space(recipient_entry.ranges.len() + 1),
+        )?;
+    }
+    recipient_entry.receive(ticket_number)?;
+    recipient_entry.total_spent = recipient_entry
+        .total_spent
+        .checked_add(spent)
+        .ok_or(LotteryError::MathOverflow)?;
+
+    emit!(TicketTransferred {
+        lottery: lottery.key(),
+        ticket_number,
+        from: holder.key(),
+        to: recipient,
+    });
+
+    msg!(
+        "Ticket {} of lottery {} transferred from {} to {}",
+        ticket_number,
+        lottery.lottery_id,
+        holder.key(),
+        recipient
+    );
+
+    Ok(())
+}
+
+/// Chooses whether tickets stop changing hands when sales close instead of
+/// at the draw. Can only be changed while tickets are on sale.
+pub fn set_transfer_lock_handler(
+    ctx: Context<SetTransferLock>,
+    locked_at_close: bool,
+) -> Result<()> {
+    let lottery = &mut ctx.accounts.lottery;
+
+    require!(lottery.state == LotteryState::Open, LotteryError::LotteryNotOpen);
+    lottery.transfers_locked_at_close = locked_at_close;
+
+    emit!(TransferLockChanged {
+        lottery: lottery.key(),
+        locked_at_close,
+    });
+
+    msg!(
+        "Lottery {} locks transfers at sales close: {}",
+        lottery.lottery_id,
+        locked_at_close
+    );
+
+    Ok(())
+}
@@ -1,13 +1,50 @@
+#![allow(deprecated)]
+// `initialize_lottery` takes every lottery setting as its own argument
+#![allow(clippy::too_many_arguments)]
+
 use anchor_lang::prelude::*;
-pub const PLATFORM_FEE_WALLET: Pubkey = pubkey!("CEYaRYc7QdEjEoojtHBS8YT8KfhUhfEaVPgdS8RcbSYF");
+/// Basis points making up 100% of a prize pool
+pub const BPS_DENOMINATOR: u16 = 10_000;
+/// Most prize tiers a lottery can be configured with
+pub const MAX_PRIZE_TIERS: usize = 10;
+/// Most numbers a player can pick on a ticket of a `Pick` lottery
+pub const MAX_PICKS: usize = 10;
+/// Largest single purchase; each ticket grows the ticket registry by 4 bytes
+/// and an account can grow by at most 10 KiB per instruction
+pub const MAX_TICKETS_PER_PURCHASE: u32 = 2_560;
+/// Largest purchase of compressed tickets, each costing a call to the compression program
+pub const MAX_COMPRESSED_TICKETS_PER_PURCHASE: u32 = 10;
+/// Largest crank reward the platform can configure, in basis points of the pool
+pub const MAX_CRANK_REWARD_BPS: u16 = 100;
+/// Keeper steps sharing the crank reward: closing sales, requesting the draw and settling
+pub const CRANK_STEPS: u64 = 3;
+/// Most oracle programs the platform can approve at once
+pub const MAX_ORACLE_PROGRAMS: usize = 4;
+/// Seconds after the sale period ends before anyone may cancel an unsettled lottery
+pub const CANCEL_GRACE_PERIOD: i64 = 7 * 24 * 60 * 60;
+/// Slots between sales closing and the slot whose hash seeds the draw
+pub const REVEAL_SLOT_DELAY: u64 = 4;
+/// Seconds the authority has to reveal its secret once sales close. The
+/// reveal slot's hash only stays in the SlotHashes sysvar for 512 slots, so
+/// anyone can record it on the lottery with `record_slot_hash` in the meantime.
+pub const REVEAL_PERIOD: i64 = 24 * 60 * 60;
+/// Slots a requested oracle draw may stay pending before the lottery can be cancelled
+pub const ORACLE_TIMEOUT_SLOTS: u64 = 1_500;
+/// Seed of the PDA an oracle program signs `fulfill_randomness` callbacks with
+pub const ORACLE_AUTHORITY_SEED: &[u8] = b"oracle-authority";
 
 pub mod instructions;
 pub mod state;
 pub mod contexts;
 pub mod errors;
+pub mod events;
+pub mod randomness;
+pub mod accounting;
+pub mod compression;
 
 use instructions::*;
 use contexts::*;
+use state::{LotteryTerms, PlatformSettings, PrizeSplit, RandomnessSource};
 
 declare_id!("3vUR35rbkJ4Wp6fMags3Scy6MLkyGmH99JpJWFYbPowQ");
 
@@ -15,14 +52,74 @@ declare_id!("3vUR35rbkJ4Wp6fMags3Scy6MLkyGmH99JpJWFYbPowQ");
 pub mod lottery {
     use super::*;
 
+    pub fn initialize_platform(
+        ctx: Context<InitializePlatform>,
+        settings: PlatformSettings,
+    ) -> Result<()> {
+        initialize_platform_handler(ctx, settings)
+    }
+
+    pub fn update_platform_config(
+        ctx: Context<UpdatePlatformConfig>,
+        settings: PlatformSettings,
+    ) -> Result<()> {
+        update_platform_config_handler(ctx, settings)
+    }
+
+    pub fn transfer_platform_admin(
+        ctx: Context<UpdatePlatformConfig>,
+        new_admin: Pubkey,
+    ) -> Result<()> {
+        transfer_platform_admin_handler(ctx, new_admin)
+    }
+
+    pub fn accept_platform_admin(ctx: Context<AcceptPlatformAdmin>) -> Result<()> {
+        accept_platform_admin_handler(ctx)
+    }
+
+    pub fn set_platform_paused(ctx: Context<UpdatePlatformConfig>, paused: bool) -> Result<()> {
+        set_platform_paused_handler(ctx, paused)
+    }
+
+    pub fn set_lottery_paused(ctx: Context<SetLotteryPaused>, paused: bool) -> Result<()> {
+        set_lottery_paused_handler(ctx, paused)
+    }
+
     pub fn initialize_lottery(
         ctx: Context<InitializeLottery>,
         lottery_id: u64,
         ticket_price: u64,
         max_tickets: u32,
         duration: u64, 
+        prize_split: PrizeSplit,
+        randomness_source: RandomnessSource,
+        ticket_nfts: bool,
     ) -> Result<()> {
-        initialize_lottery_handler(ctx, lottery_id, ticket_price, max_tickets, duration)
+        initialize_lottery_handler(
+            ctx,
+            lottery_id,
+            ticket_price,
+            max_tickets,
+            duration,
+            prize_split,
+            randomness_source,
+            ticket_nfts,
+        )
+    }
+
+    pub fn initialize_series(
+        ctx: Context<InitializeSeries>,
+        series_id: u64,
+        terms: LotteryTerms,
+    ) -> Result<()> {
+        initialize_series_handler(ctx, series_id, terms)
+    }
+
+    pub fn open_round(
+        ctx: Context<OpenRound>,
+        randomness_source: RandomnessSource,
+    ) -> Result<()> {
+        open_round_handler(ctx, randomness_source)
     }
 
     pub fn buy_ticket(
@@ -32,6 +129,66 @@ pub mod lottery {
         buy_ticket_handler(ctx, lottery_id)
     }
 
+    pub fn buy_tickets(
+        ctx: Context<BuyTicket>,
+        lottery_id: u64,
+        count: u32,
+    ) -> Result<()> {
+        buy_tickets_handler(ctx, lottery_id, count)
+    }
+
+    pub fn buy_numbers(
+        ctx: Context<BuyTicket>,
+        lottery_id: u64,
+        numbers: Vec<u8>,
+    ) -> Result<()> {
+        buy_numbers_handler(ctx, lottery_id, numbers)
+    }
+
+    pub fn buy_ticket_for(
+        ctx: Context<BuyTicketFor>,
+        lottery_id: u64,
+        recipient: Pubkey,
+    ) -> Result<()> {
+        buy_ticket_for_handler(ctx, lottery_id, recipient)
+    }
+
+    pub fn transfer_ticket(
+        ctx: Context<TransferTicket>,
+        ticket_number: u32,
+        recipient: Pubkey,
+    ) -> Result<()> {
+        transfer_ticket_handler(ctx, ticket_number, recipient)
+    }
+
+    pub fn init_ticket_tree(
+        ctx: Context<InitTicketTree>,
+        max_depth: u32,
+        max_buffer_size: u32,
+    ) -> Result<()> {
+        init_ticket_tree_handler(ctx, max_depth, max_buffer_size)
+    }
+
+    pub fn buy_compressed_tickets(
+        ctx: Context<BuyCompressedTickets>,
+        lottery_id: u64,
+        count: u32,
+    ) -> Result<()> {
+        buy_compressed_tickets_handler(ctx, lottery_id, count)
+    }
+
+    pub fn set_transfer_lock(ctx: Context<SetTransferLock>, locked_at_close: bool) -> Result<()> {
+        set_transfer_lock_handler(ctx, locked_at_close)
+    }
+
+    pub fn close_sales(ctx: Context<CloseSales>) -> Result<()> {
+        close_sales_handler(ctx)
+    }
+
+    pub fn request_randomness(ctx: Context<RequestRandomness>) -> Result<()> {
+        request_randomness_handler(ctx)
+    }
+
     pub fn fulfill_randomness(
         ctx: Context<FulfillRandomness>,
         randomness: [u8; 32],
@@ -39,7 +196,72 @@ pub mod lottery {
         fulfill_randomness_handler(ctx, randomness)
     }
 
+    pub fn record_slot_hash(ctx: Context<RecordSlotHash>) -> Result<()> {
+        record_slot_hash_handler(ctx)
+    }
+
+    pub fn reveal_randomness(
+        ctx: Context<RevealRandomness>,
+        secret: [u8; 32],
+    ) -> Result<()> {
+        reveal_randomness_handler(ctx, secret)
+    }
+
     pub fn payout(ctx: Context<Payout>) -> Result<()> {
         payout_handler(ctx)
     }
+
+    pub fn claim_prize(ctx: Context<ClaimPrize>, tier: u8) -> Result<()> {
+        claim_prize_handler(ctx, tier)
+    }
+
+    pub fn claim_numbers(ctx: Context<ClaimNumbers>) -> Result<()> {
+        claim_numbers_handler(ctx)
+    }
+
+    pub fn collect_numbers_prize(ctx: Context<CollectNumbersPrize>) -> Result<()> {
+        collect_numbers_prize_handler(ctx)
+    }
+
+    pub fn claim_compressed_prize<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ClaimCompressedPrize<'info>>,
+        tier: u8,
+        root: [u8; 32],
+        purchased_at: i64,
+    ) -> Result<()> {
+        claim_compressed_prize_handler(ctx, tier, root, purchased_at)
+    }
+
+    pub fn expire_prizes(ctx: Context<ExpirePrizes>) -> Result<()> {
+        expire_prizes_handler(ctx)
+    }
+
+    pub fn cancel_lottery(ctx: Context<CancelLottery>) -> Result<()> {
+        cancel_lottery_handler(ctx)
+    }
+
+    pub fn claim_refund<'info>(ctx: Context<'_, '_, 'info, 'info, ClaimRefund<'info>>) -> Result<()> {
+        claim_refund_handler(ctx)
+    }
+
+    pub fn claim_nft_refund(ctx: Context<ClaimNftRefund>, ticket_number: u32) -> Result<()> {
+        claim_nft_refund_handler(ctx, ticket_number)
+    }
+
+    pub fn claim_compressed_refund<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ClaimCompressedRefund<'info>>,
+        ticket_number: u32,
+        root: [u8; 32],
+        purchased_at: i64,
+    ) -> Result<()> {
+        claim_compressed_refund_handler(ctx, ticket_number, root, purchased_at)
+    }
+
+    pub fn close_ticket<'info>(ctx: Context<'_, '_, 'info, 'info, CloseTicket<'info>>) -> Result<()> {
+        close_ticket_handler(ctx)
+    }
+
+    pub fn close_lottery(ctx: Context<CloseLottery>) -> Result<()> {
+        close_lottery_handler(ctx)
+    }
 }
@@ -0,0 +1,179 @@
+//! Winner selection from a 32-byte seed.
+//!
+//! Everything here is deterministic so anyone can recompute a draw off-chain
+//! from `Lottery.randomness` and `Lottery.tickets_sold`:
+//!
+//! 1. Draw `i` uses the seed `sha256(randomness || i as u32 little-endian)`.
+//! 2. The seed is read as four little-endian u64 words. A word `x` is
+//!    rejected if `x < 2^64 mod n`, otherwise the result is `x % n`. Removing
+//!    that low range leaves a multiple of `n` values, so every index in
+//!    `[0, n)` is equally likely.
+//! 3. If all four words are rejected, the seed is replaced by `sha256(seed)`
+//!    and step 2 repeats.
+//!
+//! Lotteries with several prize tiers take draws 0, 1, 2, ... in order and
+//! skip any ticket already drawn, until every tier has a distinct winner.
+//! `Pick` lotteries draw their winning numbers the same way, as distinct
+//! values in `[0, max_number)` plus one.
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::hash::{hash, hashv};
+use crate::errors::LotteryError;
+
+/// Seed for the `index`-th draw derived from the lottery's randomness.
+pub fn draw_seed(randomness: &[u8; 32], index: u32) -> [u8; 32] {
+    hashv(&[randomness, &index.to_le_bytes()]).to_bytes()
+}
+
+/// Uniformly picks a value in `[0, n)` from `seed` by rejection sampling.
+pub fn uniform_index(seed: &[u8; 32], n: u32) -> Result<u32> {
+    require!(n > 0, LotteryError::NoTicketsSold);
+
+    let n = n as u64;
+    // 2^64 mod n, computed without overflowing
+    let threshold = n.wrapping_neg() % n;
+
+    let mut seed = *seed;
+    loop {
+        for word in seed.chunks_exact(8) {
+            let x = u64::from_le_bytes(word.try_into().unwrap());
+            if x >= threshold {
+                return Ok((x % n) as u32);
+            }
+        }
+        seed = hash(&seed).to_bytes();
+    }
+}
+
+/// The `index`-th draw over `[0, n)` from the lottery's randomness.
+pub fn draw(randomness: &[u8; 32], index: u32, n: u32) -> Result<u32> {
+    uniform_index(&draw_seed(randomness, index), n)
+}
+
+/// `count` distinct values in `[0, n)`: draws 0, 1, 2, ... in order,
+/// skipping any value already drawn.
+pub fn draw_distinct(randomness: &[u8; 32], count: u32, n: u32) -> Result<Vec<u32>> {
+    require!(count <= n, LotteryError::NoTicketsSold);
+
+    let mut drawn = Vec::with_capacity(count as usize);
+    let mut index = 0u32;
+    while drawn.len() < count as usize {
+        let value = draw(randomness, index, n)?;
+        if !drawn.contains(&value) {
+            drawn.push(value);
+        }
+        index = index.checked_add(1).ok_or(LotteryError::MathOverflow)?;
+    }
+    Ok(drawn)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn seed_from_words(words: [u64; 4]) -> [u8; 32] {
+        let mut seed = [0u8; 32];
+        for (chunk, word) in seed.chunks_exact_mut(8).zip(words) {
+            chunk.copy_from_slice(&word.to_le_bytes());
+        }
+        seed
+    }
+
+    #[test]
+    fn rejects_zero_range() {
+        assert!(uniform_index(&[7u8; 32], 0).is_err());
+    }
+
+    #[test]
+    fn single_ticket_always_wins() {
+        for i in 0..32 {
+            assert_eq!(draw(&[i as u8; 32], i, 1).unwrap(), 0);
+        }
+    }
+
+    #[test]
+    fn results_stay_in_range() {
+        let randomness = [42u8; 32];
+        for n in 1..200 {
+            for i in 0..20 {
+                assert!(draw(&randomness, i, n).unwrap() < n);
+            }
+        }
+    }
+
+    #[test]
+    fn is_deterministic() {
+        let randomness = [9u8; 32];
+        assert_eq!(draw(&randomness, 3, 1_000).unwrap(), draw(&randomness, 3, 1_000).unwrap());
+        assert_eq!(draw_seed(&randomness, 3), draw_seed(&randomness, 3));
+    }
+
+    #[test]
+    fn draws_use_independent_seeds() {
+        let randomness = [1u8; 32];
+        assert_ne!(draw_seed(&randomness, 0), draw_seed(&randomness, 1));
+        assert_ne!(draw_seed(&randomness, 0), randomness);
+    }
+
+    #[test]
+    fn skips_words_in_biased_range() {
+        // 2^64 mod 3 == 1, so a leading zero word is rejected
+        let seed = seed_from_words([0, 5, 0, 0]);
+        assert_eq!(uniform_index(&seed, 3).unwrap(), 2);
+    }
+
+    #[test]
+    fn rehashes_when_every_word_is_rejected() {
+        let seed = seed_from_words([0; 4]);
+        let expected = uniform_index(&hash(&seed).to_bytes(), 3).unwrap();
+        assert_eq!(uniform_index(&seed, 3).unwrap(), expected);
+    }
+
+    #[test]
+    fn uses_more_than_the_first_eight_bytes() {
+        let mut a = [0u8; 32];
+        let mut b = [0u8; 32];
+        a[8] = 1;
+        b[8] = 2;
+        // First words match, but both are rejected for n = 3
+        assert_ne!(uniform_index(&a, 3).unwrap(), uniform_index(&b, 3).unwrap());
+    }
+
+    #[test]
+    fn distinct_draws_never_repeat() {
+        let randomness = [3u8; 32];
+        for n in 1..30 {
+            let count = n.min(10);
+            let drawn = draw_distinct(&randomness, count, n).unwrap();
+            assert_eq!(drawn.len(), count as usize);
+            assert!(drawn.iter().all(|&value| value < n));
+            for (i, value) in drawn.iter().enumerate() {
+                assert!(!drawn[..i].contains(value));
+            }
+        }
+    }
+
+    #[test]
+    fn first_distinct_draw_matches_single_draw() {
+        let randomness = [5u8; 32];
+        assert_eq!(draw_distinct(&randomness, 3, 50).unwrap()[0], draw(&randomness, 0, 50).unwrap());
+    }
+
+    #[test]
+    fn rejects_more_distinct_draws_than_values() {
+        assert!(draw_distinct(&[5u8; 32], 4, 3).is_err());
+    }
+
+    #[test]
+    fn spreads_draws_evenly() {
+        let randomness = [0xABu8; 32];
+        let n = 10u32;
+        let mut counts = [0u32; 10];
+        for i in 0..10_000 {
+            counts[draw(&randomness, i, n).unwrap() as usize] += 1;
+        }
+        for count in counts {
+            assert!((850..1_150).contains(&count), "uneven distribution: {:?}", counts);
+        }
+    }
+}
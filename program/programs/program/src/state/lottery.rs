@@ -1,17 +1,86 @@
 use anchor_lang::prelude::*;
+use crate::errors::LotteryError;
+use crate::randomness;
+use crate::{
+    BPS_DENOMINATOR, MAX_PICKS, MAX_PRIZE_TIERS, ORACLE_TIMEOUT_SLOTS, REVEAL_PERIOD, REVEAL_SLOT_DELAY,
+};
 
+/// A single draw, seeded by `[b"lottery", lottery_id]` when created on its
+/// own or by `[b"lottery", series, round]` as a round of a `LotterySeries`,
+/// in which case `lottery_id` is the round number.
 #[account]
 pub struct Lottery {
     pub authority: Pubkey,
     pub lottery_id: u64,
+    pub series: Option<Pubkey>,
     pub ticket_price: u64,
+    pub mint: Option<Pubkey>,
     pub max_tickets: u32,
     pub tickets_sold: u32,
+    pub open_entries: u32,
     pub total_prize_pool: u64,
-    pub winner: Option<u32>,  
+    /// Part of the pool carried over from the previous round of a series
+    pub carried_over: u64,
+    /// Part of the pool taken from the series' jackpot when the round opened
+    pub jackpot: u64,
+    pub state: LotteryState,
+    pub paused: bool,
+    /// Whether tickets stop changing hands once sales close, rather than at the draw
+    pub transfers_locked_at_close: bool,
+    /// Whether every ticket is minted as an NFT whose holder claims its prize,
+    /// or its refund if the lottery is cancelled.
+    pub ticket_nfts: bool,
+    /// Merkle tree holding the tickets of a lottery sold as compressed tickets
+    pub ticket_tree: Option<Pubkey>,
+    pub draw_mode: DrawMode,
+    /// Winning ticket of each prize tier, first tier first. Empty in `Pick`
+    /// mode, where tickets are matched against `winning_numbers` instead.
+    pub winners: Vec<u32>,
+    /// Numbers drawn in `Pick` mode
+    pub winning_numbers: Vec<u8>,
+    /// Tickets registered for each prize tier in `Pick` mode
+    pub tier_winners: Vec<u32>,
+    /// Registered tickets of each prize tier already paid in `Pick` mode
+    pub tier_payments: Vec<u32>,
     pub created_at: i64,
     pub duration: u64,        
+    pub prize_split: PrizeSplit,
+    pub ticket_registry: Pubkey,
     pub randomness_fulfilled: bool,
+    pub randomness: [u8; 32],
+    pub randomness_source: RandomnessSource,
+    pub reveal_slot: u64,
+    /// Hash of `reveal_slot`, recorded while the SlotHashes sysvar still holds it
+    pub reveal_slot_hash: Option<[u8; 32]>,
+    /// Unix timestamp after which the secret can no longer be revealed
+    pub reveal_deadline: i64,
+    pub randomness_request_slot: u64,
+    pub paid_out_at: Option<i64>,
+    /// Whether the creator and platform shares have been paid
+    pub fees_paid: bool,
+    /// Set once the amounts below are fixed by the first payment
+    pub payouts_locked: bool,
+    /// Bit `i` is set once prize tier `i` has been claimed
+    pub claimed_tiers: u16,
+    pub claim_period: u64,
+    pub unclaimed_prizes: UnclaimedPrizes,
+    /// Prizes can be claimed until this timestamp, set by the draw
+    pub claim_deadline: i64,
+    /// Share of the pool reserved for the keepers who crank the lottery
+    pub crank_reward_bps: u16,
+    /// Keeper who closed sales, owed a share of the crank reward at payout
+    pub sales_closer: Option<Pubkey>,
+    /// Keeper who requested the draw or recorded the reveal slot hash
+    pub draw_requester: Option<Pubkey>,
+    /// Funds kept in the lottery for the next round of its series
+    pub rolled_over: u64,
+    /// Round that took over `rolled_over`, once opened
+    pub next_round: Option<Pubkey>,
+    /// Total paid across all prize tiers
+    pub winner_payout: u64,
+    pub creator_payout: u64,
+    pub platform_payout: u64,
+    pub crank_payout: u64,
     pub bump: u8,
 }
 
@@ -19,15 +88,513 @@ impl Space for Lottery {
     const INIT_SPACE: usize = 8 + // discriminator
         32 + // authority
         8 +  // lottery_id
+        (1 + 32) + // series (Option<Pubkey>)
         8 +  // ticket_price
+        (1 + 32) + // mint (Option<Pubkey>)
         4 +  // max_tickets
         4 +  // tickets_sold
+        4 +  // open_entries
         8 +  // total_prize_pool
-        (1 + 4) + // winner (Option<u32>)
+        8 +  // carried_over
+        8 +  // jackpot
+        1 +  // state
+        1 +  // paused
+        1 +  // transfers_locked_at_close
+        1 +  // ticket_nfts
+        (1 + 32) + // ticket_tree (Option<Pubkey>)
+        (1 + 2) + // draw_mode
+        (4 + 4 * MAX_PRIZE_TIERS) + // winners
+        (4 + MAX_PICKS) + // winning_numbers
+        (4 + 4 * MAX_PRIZE_TIERS) + // tier_winners
+        (4 + 4 * MAX_PRIZE_TIERS) + // tier_payments
         8 +  // created_at
         8 +  // duration
+        PrizeSplit::SIZE + // prize_split
+        32 + // ticket_registry
         1 +  // randomness_fulfilled
+        32 + // randomness
+        (1 + 32) + // randomness_source
+        8 +  // reveal_slot
+        (1 + 32) + // reveal_slot_hash (Option<[u8; 32]>)
+        8 +  // reveal_deadline
+        8 +  // randomness_request_slot
+        (1 + 8) + // paid_out_at (Option<i64>)
+        1 +  // fees_paid
+        1 +  // payouts_locked
+        2 +  // claimed_tiers
+        8 +  // claim_period
+        1 +  // unclaimed_prizes
+        8 +  // claim_deadline
+        2 +  // crank_reward_bps
+        (1 + 32) + // sales_closer (Option<Pubkey>)
+        (1 + 32) + // draw_requester (Option<Pubkey>)
+        8 +  // rolled_over
+        (1 + 32) + // next_round (Option<Pubkey>)
+        8 +  // winner_payout
+        8 +  // creator_payout
+        8 +  // platform_payout
+        8 +  // crank_payout
         1;   // bump
 }
 
+impl Lottery {
+    /// Calls `f` with the seeds the lottery PDA signs with.
+    pub fn with_signer_seeds<T>(&self, f: impl FnOnce(&[&[&[u8]]]) -> T) -> T {
+        let lottery_id = self.lottery_id.to_le_bytes();
+        let bump = [self.bump];
+        match &self.series {
+            None => f(&[&[b"lottery", lottery_id.as_ref(), &bump]]),
+            Some(series) => f(&[&[b"lottery", series.as_ref(), lottery_id.as_ref(), &bump]]),
+        }
+    }
 
+    /// Unix timestamp at which ticket sales end.
+    // Use i128 arithmetic to avoid incorrect casting from u64 -> i64 which can
+    // reinterpret the bits and produce huge/negative values.
+    pub fn sales_end(&self) -> Result<i128> {
+        (self.created_at as i128)
+            .checked_add(self.duration as i128)
+            .ok_or(LotteryError::LotteryClosed.into())
+    }
+
+    pub fn has_expired(&self, now: i64) -> Result<bool> {
+        Ok(now as i128 >= self.sales_end()?)
+    }
+
+    /// Whether tickets may still be transferred, which ends at the draw or,
+    /// if the authority chose so, when sales close.
+    pub fn accepts_transfers(&self) -> bool {
+        match self.state {
+            LotteryState::Open => true,
+            LotteryState::SalesClosed | LotteryState::AwaitingRandomness => {
+                !self.transfers_locked_at_close
+            }
+            _ => false,
+        }
+    }
+
+    pub fn is_sold_out(&self) -> bool {
+        self.tickets_sold >= self.max_tickets
+    }
+
+    /// Whether the lottery has reached a final state with nothing left to
+    /// pay out or refund. Funds may still be waiting in `rolled_over`.
+    pub fn is_finished(&self) -> bool {
+        matches!(self.state, LotteryState::PaidOut | LotteryState::Cancelled)
+            && self.total_prize_pool == 0
+    }
+
+    /// Whether a series round may hand over to the next one: paid out in
+    /// full, or cancelled with the refunds still owed reserved in its pool.
+    pub fn can_hand_over(&self) -> bool {
+        self.state == LotteryState::Cancelled || self.is_finished()
+    }
+
+    /// Whether the lottery is finished and its rolled over funds have moved
+    /// on, so it can be closed. A series round must stay open until the next
+    /// round has taken over from it.
+    pub fn is_settled(&self) -> bool {
+        self.is_finished()
+            && self.rolled_over == 0
+            && (self.series.is_none() || self.next_round.is_some())
+    }
+
+    /// Part of the pool that did not come from this lottery's ticket sales.
+    pub fn inherited(&self) -> Result<u64> {
+        self.carried_over
+            .checked_add(self.jackpot)
+            .ok_or(LotteryError::MathOverflow.into())
+    }
+
+    /// Cancels the lottery. Tickets are refunded from the pool, while funds
+    /// inherited from earlier rounds roll on to the next.
+    pub fn cancel(&mut self) -> Result<()> {
+        let inherited = self.inherited()?;
+        self.total_prize_pool = self
+            .total_prize_pool
+            .checked_sub(inherited)
+            .ok_or(LotteryError::InvalidPayout)?;
+        self.rolled_over = self
+            .rolled_over
+            .checked_add(inherited)
+            .ok_or(LotteryError::MathOverflow)?;
+        self.state = LotteryState::Cancelled;
+        Ok(())
+    }
+
+    /// Stops ticket sales and fixes the slot whose hash will be mixed into
+    /// the authority's revealed secret. The slot lies in the future so the
+    /// authority cannot know it when committing. The authority then has
+    /// `REVEAL_PERIOD` seconds to reveal, after which the lottery can be
+    /// cancelled by anyone and refunded.
+    pub fn close_sales(&mut self, clock: &Clock) -> Result<()> {
+        self.state = LotteryState::SalesClosed;
+        self.reveal_slot = clock
+            .slot
+            .checked_add(REVEAL_SLOT_DELAY)
+            .ok_or(LotteryError::LotteryClosed)?;
+        self.reveal_deadline = clock
+            .unix_timestamp
+            .checked_add(REVEAL_PERIOD)
+            .ok_or(LotteryError::LotteryClosed)?;
+        Ok(())
+    }
+
+    /// Last slot at which the oracle can still answer a pending request.
+    pub fn oracle_deadline(&self) -> u64 {
+        self.randomness_request_slot.saturating_add(ORACLE_TIMEOUT_SLOTS)
+    }
+
+    /// Derives one distinct winning ticket per prize tier from the final
+    /// randomness and moves the lottery to WinnerDrawn. Tickets are drawn
+    /// from those sold, or from every ticket number in `Matching` mode. If
+    /// there are fewer tickets than tiers, only the first tiers are drawn.
+    /// `Pick` mode instead draws `picks` distinct winning numbers from
+    /// `1..=max_number`. The randomness is stored so the draw can be verified
+    /// off-chain, see `crate::randomness`. Winners then have `claim_period`
+    /// seconds from `now` to claim.
+    pub fn draw_winners(&mut self, randomness: [u8; 32], now: i64) -> Result<()> {
+        let tiers = self.prize_split.tier_bps.len();
+        let range = match self.draw_mode {
+            DrawMode::Standard => self.tickets_sold,
+            DrawMode::Matching => self.max_tickets,
+            DrawMode::Pick { picks, max_number } => {
+                self.winning_numbers =
+                    randomness::draw_distinct(&randomness, picks as u32, max_number as u32)?
+                        .into_iter()
+                        .map(|number| number as u8 + 1)
+                        .collect();
+                self.tier_winners = vec![0; tiers];
+                self.tier_payments = vec![0; tiers];
+                0
+            }
+        };
+        let count = (tiers as u32).min(range);
+        self.winners = randomness::draw_distinct(&randomness, count, range)?;
+        self.claim_deadline = i64::try_from(self.claim_period)
+            .ok()
+            .and_then(|period| now.checked_add(period))
+            .ok_or(LotteryError::MathOverflow)?;
+        self.randomness = randomness;
+        self.randomness_fulfilled = true;
+        self.state = LotteryState::WinnerDrawn;
+
+        Ok(())
+    }
+
+    /// Fixes the crank reward, then the winner, creator and platform amounts
+    /// from the rest of the final `pool`. Inherited funds were already split
+    /// in an earlier round and all go to the winners. Only the first call
+    /// has any effect, so every payment after the draw works from the same
+    /// split.
+    pub fn lock_payouts(&mut self, pool: u64) -> Result<()> {
+        if self.payouts_locked {
+            return Ok(());
+        }
+
+        let inherited = self.inherited()?;
+        let sales = pool
+            .checked_sub(inherited)
+            .ok_or(LotteryError::InvalidPayout)?;
+        let crank_amount = ((sales as u128)
+            .checked_mul(self.crank_reward_bps as u128)
+            .ok_or(LotteryError::InvalidPayout)?
+            / BPS_DENOMINATOR as u128) as u64;
+        let rest = sales
+            .checked_sub(crank_amount)
+            .ok_or(LotteryError::InvalidPayout)?;
+        let (winner_amount, creator_amount, platform_amount) = self.prize_split.split(rest)?;
+        let winner_amount = winner_amount
+            .checked_add(inherited)
+            .ok_or(LotteryError::MathOverflow)?;
+        self.total_prize_pool = pool;
+        self.winner_payout = winner_amount;
+        self.creator_payout = creator_amount;
+        self.platform_payout = platform_amount;
+        self.crank_payout = crank_amount;
+        self.payouts_locked = true;
+
+        Ok(())
+    }
+
+    /// Number of prize tiers the draw produced. `Pick` draws always cover
+    /// every tier, even those nobody matches.
+    pub fn drawn_tiers(&self) -> usize {
+        match self.draw_mode {
+            DrawMode::Pick { .. } => self.tier_winners.len(),
+            _ => self.winners.len(),
+        }
+    }
+
+    /// Amount won by prize tier `tier` once payouts are locked.
+    pub fn tier_payout(&self, tier: usize) -> Result<u64> {
+        self.prize_split
+            .tier_amounts(self.winner_payout, self.drawn_tiers())?
+            .get(tier)
+            .copied()
+            .ok_or(LotteryError::InvalidPrizeTier.into())
+    }
+
+    /// Part of prize tier `tier` not paid yet. In `Pick` mode the tier is
+    /// shared equally by its registered tickets, see `tier_share`.
+    pub fn tier_remaining(&self, tier: usize) -> Result<u64> {
+        let amount = self.tier_payout(tier)?;
+        let DrawMode::Pick { .. } = self.draw_mode else {
+            return Ok(amount);
+        };
+        let paid = self.tier_share(tier)?
+            .checked_mul(self.tier_payments[tier] as u64)
+            .ok_or(LotteryError::MathOverflow)?;
+        amount
+            .checked_sub(paid)
+            .ok_or(LotteryError::InvalidPayout.into())
+    }
+
+    /// What each registered ticket of `Pick` tier `tier` receives. The last
+    /// ticket paid also takes the rounding dust.
+    pub fn tier_share(&self, tier: usize) -> Result<u64> {
+        let winners = *self.tier_winners.get(tier).ok_or(LotteryError::InvalidPrizeTier)?;
+        require!(winners > 0, LotteryError::NoWinner);
+        Ok(self.tier_payout(tier)? / winners as u64)
+    }
+
+    /// Last timestamp at which prizes can be paid to winners. `Pick` winners
+    /// register their tickets until `claim_deadline` and collect their share
+    /// during the following claim period.
+    pub fn prize_deadline(&self) -> Result<i64> {
+        match self.draw_mode {
+            DrawMode::Pick { .. } => i64::try_from(self.claim_period)
+                .ok()
+                .and_then(|period| self.claim_deadline.checked_add(period))
+                .ok_or(LotteryError::MathOverflow.into()),
+            _ => Ok(self.claim_deadline),
+        }
+    }
+
+    pub fn is_tier_claimed(&self, tier: usize) -> bool {
+        self.claimed_tiers & (1 << tier) != 0
+    }
+
+    /// Whether a sold ticket was drawn for prize tier `tier`, or registered
+    /// for it in `Pick` mode. Only `Matching` draws can pick a ticket number
+    /// nobody bought.
+    pub fn is_tier_won(&self, tier: usize) -> bool {
+        match self.draw_mode {
+            DrawMode::Pick { .. } => self.tier_winners.get(tier).is_some_and(|&count| count > 0),
+            _ => self
+                .winners
+                .get(tier)
+                .is_some_and(|&ticket| ticket < self.tickets_sold),
+        }
+    }
+
+    /// Won prize tiers not fully paid yet.
+    pub fn unclaimed_tiers(&self) -> Vec<usize> {
+        (0..self.drawn_tiers())
+            .filter(|&tier| self.is_tier_won(tier) && !self.is_tier_claimed(tier))
+            .collect()
+    }
+
+    /// Prize tiers without a winner, not yet moved to the jackpot.
+    pub fn unwon_tiers(&self) -> Vec<usize> {
+        (0..self.drawn_tiers())
+            .filter(|&tier| !self.is_tier_won(tier) && !self.is_tier_claimed(tier))
+            .collect()
+    }
+
+    /// Marks prize tier `tier` as claimed and finishes the lottery once
+    /// nothing is left to pay.
+    pub fn record_claim(&mut self, tier: usize, amount: u64, now: i64) -> Result<()> {
+        self.claimed_tiers |= 1 << tier;
+        self.record_payment(amount, now)
+    }
+
+    /// Takes `amount` off the pool and moves the lottery to PaidOut once the
+    /// fees, the crank reward and every prize tier have been paid.
+    pub fn record_payment(&mut self, amount: u64, now: i64) -> Result<()> {
+        self.total_prize_pool = self
+            .total_prize_pool
+            .checked_sub(amount)
+            .ok_or(LotteryError::InvalidPayout)?;
+
+        let all_claimed = self.claimed_tiers.count_ones() as usize == self.drawn_tiers();
+        if self.fees_paid && all_claimed {
+            self.state = LotteryState::PaidOut;
+            self.paid_out_at = Some(now);
+        }
+
+        Ok(())
+    }
+}
+
+/// Lifecycle of a lottery. Handlers only ever move a lottery forward:
+///
+/// Open -> SalesClosed -> [AwaitingRandomness] -> WinnerDrawn -> PaidOut
+///
+/// AwaitingRandomness is only used while a draw has been requested from an
+/// external source. PaidOut is reached once the fees and every prize tier
+/// have been paid. Cancelled is terminal and replaces any state before
+/// WinnerDrawn.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum LotteryState {
+    Open,
+    SalesClosed,
+    AwaitingRandomness,
+    WinnerDrawn,
+    PaidOut,
+    Cancelled,
+}
+
+/// How winning tickets are drawn, fixed at initialization.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum DrawMode {
+    /// Every prize tier is won by one of the tickets sold
+    Standard,
+    /// Winning numbers are drawn from every ticket number up to
+    /// `max_tickets`, so unsold numbers leave their tier without a winner.
+    /// The unwon prizes grow the series' jackpot. Only for series rounds.
+    Matching,
+    /// Players pick `picks` distinct numbers from `1..=max_number` and tier
+    /// `i` is shared by the tickets matching `picks - i` drawn numbers.
+    /// Tiers nobody matches grow the series' jackpot. Only for series rounds.
+    Pick { picks: u8, max_number: u8 },
+}
+
+impl DrawMode {
+    /// Checks a `Pick` mode can be drawn and pays out every one of the
+    /// `tiers` prize tiers.
+    pub fn validate(&self, tiers: usize) -> Result<()> {
+        if let DrawMode::Pick { picks, max_number } = *self {
+            require!(
+                (1..=MAX_PICKS).contains(&(picks as usize))
+                    && picks <= max_number
+                    && tiers <= picks as usize,
+                LotteryError::InvalidPickConfig
+            );
+        }
+        Ok(())
+    }
+
+    /// Validates `numbers` picked for a ticket of this mode and returns
+    /// them sorted.
+    pub fn check_picks(&self, numbers: &[u8]) -> Result<Vec<u8>> {
+        let DrawMode::Pick { picks, max_number } = *self else {
+            return err!(LotteryError::WrongDrawMode);
+        };
+        let mut sorted = numbers.to_vec();
+        sorted.sort_unstable();
+        sorted.dedup();
+        require!(
+            sorted.len() == numbers.len()
+                && numbers.len() == picks as usize
+                && sorted.iter().all(|&number| (1..=max_number).contains(&number)),
+            LotteryError::InvalidNumbers
+        );
+        Ok(sorted)
+    }
+}
+
+/// Where the randomness for the draw comes from, fixed at initialization.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum RandomnessSource {
+    /// The authority reveals the preimage of `commitment` after sales close
+    CommitReveal { commitment: [u8; 32] },
+    /// `program` delivers randomness through a CPI into `fulfill_randomness`,
+    /// signed by its `ORACLE_AUTHORITY_SEED` PDA
+    Oracle { program: Pubkey },
+}
+
+/// What happens to prizes still unclaimed once the claim period ends.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum UnclaimedPrizes {
+    /// Carried over into the next round of a series. Standalone lotteries
+    /// return them to the creator instead.
+    Rollover,
+    /// Returned to the lottery creator
+    Creator,
+    /// Sent to the platform fee wallet
+    Platform,
+}
+
+/// How a prize pool is divided, in basis points of the pool. The winners'
+/// share is further divided across prize tiers by `tier_bps`, in basis
+/// points of that share (e.g. `[6000, 2500, 1500]` for 60/25/15).
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, Debug)]
+pub struct PrizeSplit {
+    pub winner_bps: u16,
+    pub creator_bps: u16,
+    pub platform_bps: u16,
+    pub tier_bps: Vec<u16>,
+}
+
+impl PrizeSplit {
+    /// Serialized size with the maximum number of tiers
+    pub const SIZE: usize = 2 + 2 + 2 + (4 + 2 * MAX_PRIZE_TIERS);
+
+    pub fn validate(&self, min_platform_bps: u16) -> Result<()> {
+        let total_bps = self.winner_bps as u32 + self.creator_bps as u32 + self.platform_bps as u32;
+        require!(total_bps == BPS_DENOMINATOR as u32, LotteryError::InvalidPrizeSplit);
+        require!(
+            self.platform_bps >= min_platform_bps,
+            LotteryError::PlatformShareTooLow
+        );
+
+        let tier_total: u32 = self.tier_bps.iter().map(|&bps| bps as u32).sum();
+        require!(
+            (1..=MAX_PRIZE_TIERS).contains(&self.tier_bps.len())
+                && self.tier_bps.iter().all(|&bps| bps > 0)
+                && tier_total == BPS_DENOMINATOR as u32,
+            LotteryError::InvalidPrizeTiers
+        );
+        Ok(())
+    }
+
+    /// Splits `pool` into (winner, creator, platform) amounts. Winner and
+    /// creator shares are rounded down and the platform receives the
+    /// remainder, so rounding dust always goes to the same place.
+    pub fn split(&self, pool: u64) -> Result<(u64, u64, u64)> {
+        let share = |bps: u16| -> Result<u64> {
+            let amount = (pool as u128)
+                .checked_mul(bps as u128)
+                .ok_or(LotteryError::InvalidPayout)?
+                / BPS_DENOMINATOR as u128;
+            Ok(amount as u64)
+        };
+
+        let winner_amount = share(self.winner_bps)?;
+        let creator_amount = share(self.creator_bps)?;
+        let platform_amount = pool
+            .checked_sub(winner_amount)
+            .and_then(|rest| rest.checked_sub(creator_amount))
+            .ok_or(LotteryError::InvalidPayout)?;
+
+        Ok((winner_amount, creator_amount, platform_amount))
+    }
+
+    /// Splits `winner_amount` across the first `filled` tiers. Tiers round
+    /// down; the first tier takes the rounding dust along with the share of
+    /// any tier left unfilled.
+    pub fn tier_amounts(&self, winner_amount: u64, filled: usize) -> Result<Vec<u64>> {
+        let mut amounts = self.tier_bps[..filled.min(self.tier_bps.len())]
+            .iter()
+            .map(|&bps| {
+                (winner_amount as u128)
+                    .checked_mul(bps as u128)
+                    .map(|amount| (amount / BPS_DENOMINATOR as u128) as u64)
+                    .ok_or(LotteryError::InvalidPayout.into())
+            })
+            .collect::<Result<Vec<u64>>>()?;
+
+        if !amounts.is_empty() {
+            let others = amounts[1..]
+                .iter()
+                .try_fold(0u64, |sum, &amount| sum.checked_add(amount))
+                .ok_or(LotteryError::InvalidPayout)?;
+            amounts[0] = winner_amount
+                .checked_sub(others)
+                .ok_or(LotteryError::InvalidPayout)?;
+        }
+
+        Ok(amounts)
+    }
+}
@@ -0,0 +1,69 @@
+use anchor_lang::prelude::*;
+use crate::state::{DrawMode, PrizeSplit};
+
+/// A recurring lottery, seeded by `[b"series", series_id]`. Each round is a
+/// regular `Lottery` seeded by `[b"lottery", series, round]` and opened from
+/// the series' terms once the previous round has settled.
+#[account]
+pub struct LotterySeries {
+    pub authority: Pubkey,
+    pub series_id: u64,
+    pub mint: Option<Pubkey>,
+    pub terms: LotteryTerms,
+    /// Rounds opened so far, which is also the number of the next round
+    pub rounds: u64,
+    /// Latest round, `Pubkey::default()` until the first one opens
+    pub current_round: Pubkey,
+    pub bump: u8,
+}
+
+impl Space for LotterySeries {
+    const INIT_SPACE: usize = 8 + // discriminator
+        32 + // authority
+        8 +  // series_id
+        (1 + 32) + // mint (Option<Pubkey>)
+        LotteryTerms::SIZE + // terms
+        8 +  // rounds
+        32 + // current_round
+        1;   // bump
+}
+
+/// Sale terms of a lottery, shared by every round of a series.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, Debug)]
+pub struct LotteryTerms {
+    pub ticket_price: u64,
+    pub max_tickets: u32,
+    pub duration: u64,
+    pub prize_split: PrizeSplit,
+    pub draw_mode: DrawMode,
+}
+
+impl LotteryTerms {
+    /// Serialized size with the maximum number of prize tiers
+    pub const SIZE: usize = 8 + 4 + 8 + PrizeSplit::SIZE + (1 + 2);
+}
+
+/// Prizes no ticket won in `Matching` and `Pick` rounds of a series, seeded by
+/// `[b"jackpot", series]`. It holds SOL in its own lamports, or tokens in a
+/// vault seeded by `[b"vault", jackpot]`, until the next round opens and
+/// takes it all.
+#[account]
+pub struct Jackpot {
+    pub series: Pubkey,
+    pub amount: u64,
+    pub bump: u8,
+}
+
+impl Space for Jackpot {
+    const INIT_SPACE: usize = 8 + // discriminator
+        32 + // series
+        8 +  // amount
+        1;   // bump
+}
+
+/// Token vault of the jackpot of `series`, seeded by `[b"vault", jackpot]`.
+pub fn jackpot_vault_address(series: &Pubkey) -> Pubkey {
+    let (jackpot, _) =
+        Pubkey::find_program_address(&[b"jackpot", series.as_ref()], &crate::ID);
+    Pubkey::find_program_address(&[b"vault", jackpot.as_ref()], &crate::ID).0
+}
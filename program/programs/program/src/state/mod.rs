@@ -1,5 +1,13 @@
 pub mod lottery;
+pub mod lottery_series;
+pub mod player_entry;
+pub mod platform_config;
 pub mod ticket;
+pub mod ticket_registry;
 
 pub use lottery::*;
+pub use lottery_series::*;
+pub use player_entry::*;
+pub use platform_config::*;
 pub use ticket::*;
+pub use ticket_registry::*;
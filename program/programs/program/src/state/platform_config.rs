@@ -0,0 +1,121 @@
+use anchor_lang::prelude::*;
+use crate::errors::LotteryError;
+use crate::state::UnclaimedPrizes;
+use crate::{BPS_DENOMINATOR, MAX_CRANK_REWARD_BPS, MAX_ORACLE_PROGRAMS};
+
+/// Program-wide settings, stored in a single PDA seeded by `platform-config`.
+#[account]
+pub struct PlatformConfig {
+    pub admin: Pubkey,
+    /// Admin nominated by `transfer_platform_admin`, who takes over once
+    /// they accept
+    pub pending_admin: Option<Pubkey>,
+    pub fee_wallet: Pubkey,
+    pub default_fee_bps: u16,
+    pub min_fee_bps: u16,
+    pub min_ticket_price: u64,
+    pub max_ticket_price: u64,
+    pub min_duration: u64,
+    pub max_duration: u64,
+    pub claim_period: u64,
+    pub unclaimed_prizes: UnclaimedPrizes,
+    pub crank_reward_bps: u16,
+    pub oracle_programs: Vec<Pubkey>,
+    pub paused: bool,
+    pub bump: u8,
+}
+
+impl Space for PlatformConfig {
+    const INIT_SPACE: usize = 8 + // discriminator
+        32 + // admin
+        (1 + 32) + // pending_admin (Option<Pubkey>)
+        32 + // fee_wallet
+        2 +  // default_fee_bps
+        2 +  // min_fee_bps
+        8 +  // min_ticket_price
+        8 +  // max_ticket_price
+        8 +  // min_duration
+        8 +  // max_duration
+        8 +  // claim_period
+        1 +  // unclaimed_prizes
+        2 +  // crank_reward_bps
+        4 + 32 * MAX_ORACLE_PROGRAMS + // oracle_programs
+        1 +  // paused
+        1;   // bump
+}
+
+impl PlatformConfig {
+    pub fn apply(&mut self, settings: &PlatformSettings) -> Result<()> {
+        settings.validate()?;
+
+        self.fee_wallet = settings.fee_wallet;
+        self.default_fee_bps = settings.default_fee_bps;
+        self.min_fee_bps = settings.min_fee_bps;
+        self.min_ticket_price = settings.min_ticket_price;
+        self.max_ticket_price = settings.max_ticket_price;
+        self.min_duration = settings.min_duration;
+        self.max_duration = settings.max_duration;
+        self.claim_period = settings.claim_period;
+        self.unclaimed_prizes = settings.unclaimed_prizes;
+        self.crank_reward_bps = settings.crank_reward_bps;
+        self.oracle_programs = settings.oracle_programs.clone();
+
+        Ok(())
+    }
+}
+
+/// Admin-controlled values of `PlatformConfig`, shared by
+/// `initialize_platform` and `update_platform_config`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, Debug)]
+pub struct PlatformSettings {
+    pub fee_wallet: Pubkey,
+    /// Platform share offered to creators who do not choose their own
+    pub default_fee_bps: u16,
+    /// Smallest platform share any lottery may be created with
+    pub min_fee_bps: u16,
+    pub min_ticket_price: u64,
+    pub max_ticket_price: u64,
+    pub min_duration: u64,
+    pub max_duration: u64,
+    /// Seconds winners have to claim their prizes after the draw
+    pub claim_period: u64,
+    /// Where prizes still unclaimed after the claim period go
+    pub unclaimed_prizes: UnclaimedPrizes,
+    /// Share of the pool paid to the keepers who close sales, request the
+    /// draw and settle a lottery, split evenly when `payout` runs. Nothing
+    /// is paid before then, as a lottery can still be cancelled until it is
+    /// drawn and must then refund every ticket in full.
+    pub crank_reward_bps: u16,
+    /// Oracle programs lotteries may take their randomness from
+    pub oracle_programs: Vec<Pubkey>,
+}
+
+impl PlatformSettings {
+    pub fn validate(&self) -> Result<()> {
+        require!(
+            self.min_fee_bps <= self.default_fee_bps && self.default_fee_bps <= BPS_DENOMINATOR,
+            LotteryError::InvalidPlatformConfig
+        );
+        require!(
+            self.min_ticket_price <= self.max_ticket_price,
+            LotteryError::InvalidPlatformConfig
+        );
+        require!(
+            self.min_duration <= self.max_duration,
+            LotteryError::InvalidPlatformConfig
+        );
+        require!(
+            self.claim_period > 0 && i64::try_from(self.claim_period).is_ok(),
+            LotteryError::InvalidPlatformConfig
+        );
+        require!(
+            self.crank_reward_bps <= MAX_CRANK_REWARD_BPS,
+            LotteryError::InvalidPlatformConfig
+        );
+        require!(
+            self.oracle_programs.len() <= MAX_ORACLE_PROGRAMS,
+            LotteryError::InvalidPlatformConfig
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,249 @@
+use anchor_lang::prelude::*;
+use crate::errors::LotteryError;
+
+/// Every ticket a player holds in one lottery, seeded by
+/// `[b"player-entry", lottery, player]`. Grows by one range whenever a
+/// purchase does not directly follow the player's previous one.
+#[account]
+pub struct PlayerEntry {
+    pub lottery: Pubkey,
+    pub player: Pubkey,
+    /// Owner index used for this player in the lottery's `TicketRegistry`
+    pub index: u32,
+    pub ticket_count: u32,
+    /// What this entry added to the prize pool, net of any token transfer fee
+    pub total_spent: u64,
+    pub ranges: Vec<TicketRange>,
+    pub bump: u8,
+}
+
+impl PlayerEntry {
+    /// Account size holding `ranges` ticket ranges.
+    pub fn space(ranges: usize) -> usize {
+        8 + // discriminator
+        32 + // lottery
+        32 + // player
+        4 +  // index
+        4 +  // ticket_count
+        8 +  // total_spent
+        4 + ranges * TicketRange::SIZE + // ranges
+        1    // bump
+    }
+
+    /// Whether recording `start` needs room for an extra range.
+    pub fn needs_new_range(&self, start: u32) -> bool {
+        !matches!(self.ranges.last(), Some(last) if last.end() == start)
+    }
+
+    /// Appends `count` tickets starting at `start`, merging with the last
+    /// range when the numbers are contiguous.
+    pub fn record(&mut self, start: u32, count: u32) -> Result<()> {
+        self.ticket_count = self
+            .ticket_count
+            .checked_add(count)
+            .ok_or(LotteryError::MathOverflow)?;
+        match self.ranges.last_mut() {
+            Some(last) if last.end() == start => {
+                last.count = last.count.checked_add(count).ok_or(LotteryError::MathOverflow)?
+            }
+            _ => self.ranges.push(TicketRange { start, count }),
+        }
+        Ok(())
+    }
+
+    pub fn owns(&self, number: u32) -> bool {
+        self.ranges.iter().any(|range| range.contains(number))
+    }
+
+    /// Gives up one ticket's share of `total_spent` for its refund, returning
+    /// the amount. The last ticket takes whatever rounding left over.
+    pub fn refund_one(&mut self) -> Result<u64> {
+        require!(self.ticket_count > 0, LotteryError::TicketNotOwned);
+        let amount = self.total_spent / self.ticket_count as u64;
+        self.ticket_count -= 1;
+        self.total_spent -= amount;
+        Ok(amount)
+    }
+
+    /// Whether giving up `number` splits its range in two, needing room for
+    /// an extra range.
+    pub fn splits_at(&self, number: u32) -> bool {
+        self.ranges
+            .iter()
+            .any(|range| range.contains(number) && number != range.start && number + 1 != range.end())
+    }
+
+    /// Removes the ticket `number`, returning whether the entry held it.
+    pub fn release(&mut self, number: u32) -> Result<bool> {
+        let Some(position) = self.ranges.iter().position(|range| range.contains(number)) else {
+            return Ok(false);
+        };
+        self.ticket_count = self
+            .ticket_count
+            .checked_sub(1)
+            .ok_or(LotteryError::MathOverflow)?;
+        let range = self.ranges[position];
+        if range.count == 1 {
+            self.ranges.remove(position);
+        } else if number == range.start {
+            self.ranges[position] = TicketRange { start: number + 1, count: range.count - 1 };
+        } else if number + 1 == range.end() {
+            self.ranges[position].count -= 1;
+        } else {
+            self.ranges[position].count = number - range.start;
+            self.ranges.push(TicketRange { start: number + 1, count: range.end() - number - 1 });
+        }
+        Ok(true)
+    }
+
+    /// Whether receiving `number` needs room for an extra range, because no
+    /// range ends right before it or starts right after it.
+    pub fn needs_range_for(&self, number: u32) -> bool {
+        !self
+            .ranges
+            .iter()
+            .any(|range| range.end() == number || number + 1 == range.start)
+    }
+
+    /// Adds the single ticket `number`, extending an adjacent range if there is one.
+    pub fn receive(&mut self, number: u32) -> Result<()> {
+        self.ticket_count = self
+            .ticket_count
+            .checked_add(1)
+            .ok_or(LotteryError::MathOverflow)?;
+        match self
+            .ranges
+            .iter_mut()
+            .find(|range| range.end() == number || number + 1 == range.start)
+        {
+            Some(range) => {
+                if range.end() != number {
+                    range.start = number;
+                }
+                range.count = range.count.checked_add(1).ok_or(LotteryError::MathOverflow)?;
+            }
+            None => self.ranges.push(TicketRange { start: number, count: 1 }),
+        }
+        Ok(())
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct TicketRange {
+    pub start: u32,
+    pub count: u32,
+}
+
+impl TicketRange {
+    pub const SIZE: usize = 4 + 4;
+
+    /// One past the last ticket number in this range.
+    pub fn end(&self) -> u32 {
+        self.start.saturating_add(self.count)
+    }
+
+    pub fn contains(&self, number: u32) -> bool {
+        (self.start..self.end()).contains(&number)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(ranges: &[(u32, u32)]) -> PlayerEntry {
+        let ranges: Vec<TicketRange> = ranges
+            .iter()
+            .map(|&(start, count)| TicketRange { start, count })
+            .collect();
+        PlayerEntry {
+            lottery: Pubkey::default(),
+            player: Pubkey::default(),
+            index: 0,
+            ticket_count: ranges.iter().map(|range| range.count).sum(),
+            total_spent: 0,
+            ranges,
+            bump: 0,
+        }
+    }
+
+    fn ranges(entry: &PlayerEntry) -> Vec<(u32, u32)> {
+        entry.ranges.iter().map(|range| (range.start, range.count)).collect()
+    }
+
+    #[test]
+    fn splits_only_inside_a_range() {
+        let entry = entry(&[(10, 5)]);
+        assert!(!entry.splits_at(10));
+        assert!(!entry.splits_at(14));
+        assert!(entry.splits_at(12));
+        assert!(!entry.splits_at(15));
+        assert!(!entry.splits_at(9));
+    }
+
+    #[test]
+    fn release_trims_range_start() {
+        let mut entry = entry(&[(10, 5)]);
+        assert!(entry.release(10).unwrap());
+        assert_eq!(ranges(&entry), [(11, 4)]);
+        assert_eq!(entry.ticket_count, 4);
+    }
+
+    #[test]
+    fn release_trims_range_end() {
+        let mut entry = entry(&[(10, 5)]);
+        assert!(entry.release(14).unwrap());
+        assert_eq!(ranges(&entry), [(10, 4)]);
+        assert_eq!(entry.ticket_count, 4);
+    }
+
+    #[test]
+    fn release_splits_range_in_the_middle() {
+        let mut entry = entry(&[(10, 5)]);
+        assert!(entry.release(12).unwrap());
+        assert_eq!(ranges(&entry), [(10, 2), (13, 2)]);
+        assert_eq!(entry.ticket_count, 4);
+    }
+
+    #[test]
+    fn release_removes_single_ticket_range() {
+        let mut entry = entry(&[(3, 1), (10, 2)]);
+        assert!(entry.release(3).unwrap());
+        assert_eq!(ranges(&entry), [(10, 2)]);
+    }
+
+    #[test]
+    fn release_ignores_tickets_not_held() {
+        let mut entry = entry(&[(10, 5)]);
+        assert!(!entry.release(15).unwrap());
+        assert_eq!(ranges(&entry), [(10, 5)]);
+        assert_eq!(entry.ticket_count, 5);
+    }
+
+    #[test]
+    fn receive_extends_range_ending_before() {
+        let mut entry = entry(&[(10, 5)]);
+        assert!(!entry.needs_range_for(15));
+        entry.receive(15).unwrap();
+        assert_eq!(ranges(&entry), [(10, 6)]);
+        assert_eq!(entry.ticket_count, 6);
+    }
+
+    #[test]
+    fn receive_extends_range_starting_after() {
+        let mut entry = entry(&[(10, 5)]);
+        assert!(!entry.needs_range_for(9));
+        entry.receive(9).unwrap();
+        assert_eq!(ranges(&entry), [(9, 6)]);
+        assert_eq!(entry.ticket_count, 6);
+    }
+
+    #[test]
+    fn receive_adds_range_when_not_adjacent() {
+        let mut entry = entry(&[(10, 5)]);
+        assert!(entry.needs_range_for(20));
+        entry.receive(20).unwrap();
+        assert_eq!(ranges(&entry), [(10, 5), (20, 1)]);
+        assert_eq!(entry.ticket_count, 6);
+    }
+}
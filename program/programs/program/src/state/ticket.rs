@@ -1,19 +1,35 @@
 use anchor_lang::prelude::*;
+use crate::MAX_PICKS;
 
+/// Numbers picked for one ticket of a `Pick` lottery, seeded by
+/// `[b"ticket", lottery, ticket_number]`. The ticket belongs to whoever owns
+/// its number in the lottery's `TicketRegistry`.
 #[account]
 pub struct Ticket {
     pub lottery: Pubkey,
-    pub player: Pubkey,
     pub ticket_number: u32,
-    pub purchased_at: i64,
+    /// Picked numbers, in ascending order
+    pub numbers: Vec<u8>,
+    /// Prize tier the ticket is registered for by `claim_numbers`
+    pub tier: Option<u8>,
     pub bump: u8,
 }
 
 impl Space for Ticket {
     const INIT_SPACE: usize = 8 + // discriminator
         32 + // lottery
-        32 + // player
         4 +  // ticket_number
-        8 +  // purchased_at
+        (4 + MAX_PICKS) + // numbers
+        (1 + 1) + // tier (Option<u8>)
         1;   // bump
 }
+
+/// Mint of the NFT issued for `ticket_number` by a lottery with `ticket_nfts`,
+/// seeded by `[b"ticket-mint", lottery, ticket_number]`.
+pub fn ticket_mint_address(lottery: &Pubkey, ticket_number: u32) -> Pubkey {
+    Pubkey::find_program_address(
+        &[b"ticket-mint", lottery.as_ref(), ticket_number.to_le_bytes().as_ref()],
+        &crate::ID,
+    )
+    .0
+}
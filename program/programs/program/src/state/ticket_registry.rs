@@ -0,0 +1,40 @@
+use anchor_lang::prelude::*;
+
+/// Maps every ticket number of a lottery to the `PlayerEntry.index` of its
+/// owner, seeded by `[b"ticket-registry", lottery]`.
+///
+/// The zero-copy header is followed by one little-endian u32 owner index per
+/// ticket sold. The account is reallocated as tickets are bought, so lookups
+/// never need to deserialize more than the header.
+#[account(zero_copy)]
+pub struct TicketRegistry {
+    pub lottery: Pubkey,
+    pub ticket_count: u32,
+    pub player_count: u32,
+}
+
+impl TicketRegistry {
+    pub const HEADER_SIZE: usize = 8 + std::mem::size_of::<TicketRegistry>();
+    const OWNER_SIZE: usize = 4;
+
+    /// Account size holding owners for `tickets` tickets.
+    pub fn space(tickets: u32) -> usize {
+        Self::HEADER_SIZE + tickets as usize * Self::OWNER_SIZE
+    }
+
+    /// Records `owner` for tickets `start..start + count` in the raw account data.
+    pub fn assign(data: &mut [u8], start: u32, count: u32, owner: u32) {
+        let from = Self::space(start);
+        let to = Self::space(start + count);
+        for slot in data[from..to].chunks_exact_mut(Self::OWNER_SIZE) {
+            slot.copy_from_slice(&owner.to_le_bytes());
+        }
+    }
+
+    /// Owner index of `ticket` from the raw account data.
+    pub fn owner_of(data: &[u8], ticket: u32) -> Option<u32> {
+        let from = Self::space(ticket);
+        data.get(from..from + Self::OWNER_SIZE)
+            .map(|bytes| u32::from_le_bytes(bytes.try_into().unwrap()))
+    }
+}
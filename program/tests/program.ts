@@ -1,15 +1,45 @@
 import * as anchor from "@coral-xyz/anchor";
 import { Program } from "@coral-xyz/anchor";
 import { Lottery } from "../target/types/lottery";
+import { MockOracle } from "../target/types/mock_oracle";
 import { expect } from "chai";
-import { Keypair, LAMPORTS_PER_SOL, PublicKey, SystemProgram } from "@solana/web3.js";
+import {
+  Keypair,
+  LAMPORTS_PER_SOL,
+  PublicKey,
+  SystemProgram,
+  SYSVAR_CLOCK_PUBKEY,
+  SYSVAR_SLOT_HASHES_PUBKEY,
+  Transaction,
+} from "@solana/web3.js";
+import {
+  ASSOCIATED_TOKEN_PROGRAM_ID,
+  ExtensionType,
+  TOKEN_2022_PROGRAM_ID,
+  TOKEN_PROGRAM_ID,
+  createInitializeMintInstruction,
+  createInitializePermanentDelegateInstruction,
+  createInitializeTransferFeeConfigInstruction,
+  createMint,
+  getAccount,
+  getAssociatedTokenAddressSync,
+  getMint,
+  getMintLen,
+  getOrCreateAssociatedTokenAccount,
+  getTokenMetadata,
+  mintTo,
+  transferChecked,
+} from "@solana/spl-token";
+import { keccak_256 } from "@noble/hashes/sha3";
+import { createHash, randomBytes } from "crypto";
 
 describe("lottery", () => {
-  // Configure the client to use devnet
+  // Configure the client to use the local validator started by `anchor test`
   const provider = anchor.AnchorProvider.env();
   anchor.setProvider(provider);
 
   const program = anchor.workspace.Lottery as Program<Lottery>;
+  const mockOracle = anchor.workspace.MockOracle as Program<MockOracle>;
   
   // Test accounts
   let authority: Keypair;
@@ -19,7 +49,95 @@ describe("lottery", () => {
   
   // Test parameters
   const ticketPrice = new anchor.BN(0.01 * LAMPORTS_PER_SOL); // Reduced to 0.01 SOL for testing
-  const maxTickets = 10;
+  const maxTickets = 3; // Selling every ticket closes sales without waiting for expiry
+  const duration = new anchor.BN(60 * 60); // 1 hour
+  const prizeSplit = { winnerBps: 9000, creatorBps: 500, platformBps: 500, tierBps: [10000] }; // 90/5/5
+  const tieredSplit = { ...prizeSplit, tierBps: [6000, 2500, 1500] }; // 1st 60%, 2nd 25%, 3rd 15%
+
+  // Read from the platform config once it exists
+  let platformFeeWallet: PublicKey;
+  const [platformConfigPda] = PublicKey.findProgramAddressSync(
+    [Buffer.from("platform-config")],
+    program.programId
+  );
+
+  // Commit-reveal secret; the lottery only stores its sha256 hash
+  const secret = randomBytes(32);
+  const commitment = Array.from(createHash("sha256").update(secret).digest());
+  const commitReveal = { commitReveal: { commitment } };
+
+  // Off-chain replica of `randomness::draw`, used to verify draws
+  const drawIndex = (randomness: number[], index: number, n: number): number => {
+    const indexBytes = Buffer.alloc(4);
+    indexBytes.writeUInt32LE(index);
+    let seed = createHash("sha256").update(Buffer.from(randomness)).update(indexBytes).digest();
+    const bigN = BigInt(n);
+    const threshold = (BigInt(1) << BigInt(64)) % bigN;
+    for (;;) {
+      for (let offset = 0; offset < 32; offset += 8) {
+        const x = seed.readBigUInt64LE(offset);
+        if (x >= threshold) return Number(x % bigN);
+      }
+      seed = createHash("sha256").update(seed).digest();
+    }
+  };
+
+  // Off-chain replica of `randomness::draw_distinct`
+  const drawDistinct = (randomness: number[], count: number, n: number): number[] => {
+    const drawn: number[] = [];
+    for (let index = 0; drawn.length < count; index++) {
+      const value = drawIndex(randomness, index, n);
+      if (!drawn.includes(value)) drawn.push(value);
+    }
+    return drawn;
+  };
+
+  const findPlayerEntry = (lottery: PublicKey, player: PublicKey) =>
+    PublicKey.findProgramAddressSync(
+      [Buffer.from("player-entry"), lottery.toBuffer(), player.toBuffer()],
+      program.programId
+    )[0];
+
+  const findTicketRegistry = (lottery: PublicKey) =>
+    PublicKey.findProgramAddressSync(
+      [Buffer.from("ticket-registry"), lottery.toBuffer()],
+      program.programId
+    )[0];
+
+  // Current platform settings, to change one temporarily and restore it
+  const fetchPlatformSettings = async () => {
+    const config = await program.account.platformConfig.fetch(platformConfigPda);
+    return {
+      feeWallet: config.feeWallet,
+      defaultFeeBps: config.defaultFeeBps,
+      minFeeBps: config.minFeeBps,
+      minTicketPrice: config.minTicketPrice,
+      maxTicketPrice: config.maxTicketPrice,
+      minDuration: config.minDuration,
+      maxDuration: config.maxDuration,
+      claimPeriod: config.claimPeriod,
+      unclaimedPrizes: config.unclaimedPrizes,
+      crankRewardBps: config.crankRewardBps,
+      oraclePrograms: config.oraclePrograms,
+    };
+  };
+
+  const waitForSlot = async (slot: number) => {
+    while ((await provider.connection.getSlot()) <= slot) {
+      await new Promise(resolve => setTimeout(resolve, 400));
+    }
+  };
+
+  // Waits until the validator's clock, rather than the local one, is past `timestamp`
+  const waitForTimestamp = async (timestamp: number) => {
+    const clockTimestamp = async () => {
+      const clock = await provider.connection.getAccountInfo(SYSVAR_CLOCK_PUBKEY);
+      return Number(clock.data.readBigInt64LE(32));
+    };
+    while ((await clockTimestamp()) <= timestamp) {
+      await new Promise(resolve => setTimeout(resolve, 400));
+    }
+  };
 
   before(async () => {
     // Generate test keypairs
@@ -34,10 +152,10 @@ describe("lottery", () => {
     console.log(`Player2: ${player2.publicKey.toString()}`);
     console.log(`Player3: ${player3.publicKey.toString()}`);
     console.log("\nIf airdrops fail, fund these accounts manually with:");
-    console.log(`solana airdrop 2 ${authority.publicKey.toString()} --url devnet`);
-    console.log(`solana airdrop 2 ${player1.publicKey.toString()} --url devnet`);
-    console.log(`solana airdrop 2 ${player2.publicKey.toString()} --url devnet`);
-    console.log(`solana airdrop 2 ${player3.publicKey.toString()} --url devnet`);
+    console.log(`solana airdrop 2 ${authority.publicKey.toString()} --url localhost`);
+    console.log(`solana airdrop 2 ${player1.publicKey.toString()} --url localhost`);
+    console.log(`solana airdrop 2 ${player2.publicKey.toString()} --url localhost`);
+    console.log(`solana airdrop 2 ${player3.publicKey.toString()} --url localhost`);
 
     // Simple airdrop attempt - if it fails, the user can fund manually
     try {
@@ -94,17 +212,45 @@ describe("lottery", () => {
     } else {
       console.log("\n✅ All accounts have sufficient balance for testing");
     }
+
+    // The platform config is a singleton that only the upgrade authority
+    // (the provider wallet that deployed the program) can create
+    let platformConfig = await program.account.platformConfig.fetchNullable(platformConfigPda);
+    if (!platformConfig) {
+      const [programData] = PublicKey.findProgramAddressSync(
+        [program.programId.toBuffer()],
+        new PublicKey("BPFLoaderUpgradeab1e11111111111111111111111")
+      );
+
+      await program.methods
+        .initializePlatform({
+          feeWallet: Keypair.generate().publicKey,
+          defaultFeeBps: 500,
+          minFeeBps: 500,
+          minTicketPrice: new anchor.BN(1000),
+          maxTicketPrice: new anchor.BN(100 * LAMPORTS_PER_SOL),
+          minDuration: new anchor.BN(60),
+          maxDuration: new anchor.BN(30 * 24 * 60 * 60),
+          claimPeriod: new anchor.BN(30 * 24 * 60 * 60),
+          unclaimedPrizes: { rollover: {} },
+          crankRewardBps: 0,
+          oraclePrograms: [mockOracle.programId],
+        })
+        .accounts({ programData })
+        .rpc();
+
+      platformConfig = await program.account.platformConfig.fetch(platformConfigPda);
+    }
+    platformFeeWallet = platformConfig.feeWallet;
   });
 
   describe("Complete Lottery Flow", () => {
     let lotteryId: anchor.BN;
     let lotteryPda: PublicKey;
     let lotteryBump: number;
-    let ticket1Pda: PublicKey;
-    let ticket2Pda: PublicKey;
-    let ticket3Pda: PublicKey;
+    let payoutAccounts: Record<string, PublicKey>;
 
-    beforeEach(async () => {
+    before(async () => {
       // Use timestamp to ensure unique lottery IDs
       lotteryId = new anchor.BN(Date.now());
       
@@ -117,7 +263,7 @@ describe("lottery", () => {
 
     it("Should initialize a new lottery", async () => {
       await program.methods
-        .initializeLottery(lotteryId, ticketPrice, maxTickets)
+        .initializeLottery(lotteryId, ticketPrice, maxTickets, duration, tieredSplit, commitReveal, false)
         .accounts({
           authority: authority.publicKey,
           lottery: lotteryPda,
@@ -134,22 +280,15 @@ describe("lottery", () => {
       expect(lottery.maxTickets).to.equal(maxTickets);
       expect(lottery.ticketsSold).to.equal(0);
       expect(lottery.totalPrizePool.toString()).to.equal("0");
-      expect(lottery.state).to.deep.equal({ waitingForTickets: {} });
-      expect(lottery.winner).to.be.null;
+      expect(lottery.state).to.deep.equal({ open: {} });
+      expect(lottery.winners).to.be.empty;
+      expect(lottery.prizeSplit.tierBps).to.deep.equal(tieredSplit.tierBps);
       expect(lottery.randomnessFulfilled).to.be.false;
       expect(lottery.bump).to.equal(lotteryBump);
     });
 
     it("Should allow player1 to buy a ticket", async () => {
-      // Find ticket PDA - use current tickets_sold which is 0
-      [ticket1Pda] = PublicKey.findProgramAddressSync(
-        [
-          Buffer.from("ticket"), 
-          lotteryPda.toBuffer(), 
-          Buffer.from([0, 0, 0, 0]) // tickets_sold = 0 before this purchase
-        ],
-        program.programId
-      );
+      const entryPda = findPlayerEntry(lotteryPda, player1.publicKey);
 
       const initialBalance = await provider.connection.getBalance(player1.publicKey);
       
@@ -158,17 +297,19 @@ describe("lottery", () => {
         .accounts({
           player: player1.publicKey,
           lottery: lotteryPda,
-          ticket: ticket1Pda,
+          playerEntry: entryPda,
+          ticketRegistry: findTicketRegistry(lotteryPda),
           systemProgram: SystemProgram.programId,
         })
         .signers([player1])
         .rpc();
 
-      // Verify ticket
-      const ticket = await program.account.ticket.fetch(ticket1Pda);
-      expect(ticket.lottery.toString()).to.equal(lotteryPda.toString());
-      expect(ticket.player.toString()).to.equal(player1.publicKey.toString());
-      expect(ticket.ticketNumber).to.equal(0);
+      // Verify the player's entry records ticket 0
+      const entry = await program.account.playerEntry.fetch(entryPda);
+      expect(entry.lottery.toString()).to.equal(lotteryPda.toString());
+      expect(entry.player.toString()).to.equal(player1.publicKey.toString());
+      expect(entry.ticketCount).to.equal(1);
+      expect(entry.ranges).to.deep.equal([{ start: 0, count: 1 }]);
 
       // Verify lottery state updated
       const lottery = await program.account.lottery.fetch(lotteryPda);
@@ -181,21 +322,13 @@ describe("lottery", () => {
     });
 
     it("Should allow player2 to buy a ticket", async () => {
-      [ticket2Pda] = PublicKey.findProgramAddressSync(
-        [
-          Buffer.from("ticket"), 
-          lotteryPda.toBuffer(), 
-          Buffer.from([1, 0, 0, 0]) // tickets_sold = 1 before this purchase
-        ],
-        program.programId
-      );
-
       await program.methods
         .buyTicket(lotteryId)
         .accounts({
           player: player2.publicKey,
           lottery: lotteryPda,
-          ticket: ticket2Pda,
+          playerEntry: findPlayerEntry(lotteryPda, player2.publicKey),
+          ticketRegistry: findTicketRegistry(lotteryPda),
           systemProgram: SystemProgram.programId,
         })
         .signers([player2])
@@ -208,21 +341,13 @@ describe("lottery", () => {
     });
 
     it("Should allow player3 to buy a ticket", async () => {
-      [ticket3Pda] = PublicKey.findProgramAddressSync(
-        [
-          Buffer.from("ticket"), 
-          lotteryPda.toBuffer(), 
-          Buffer.from([2, 0, 0, 0]) // tickets_sold = 2 before this purchase
-        ],
-        program.programId
-      );
-
       await program.methods
         .buyTicket(lotteryId)
         .accounts({
           player: player3.publicKey,
           lottery: lotteryPda,
-          ticket: ticket3Pda,
+          playerEntry: findPlayerEntry(lotteryPda, player3.publicKey),
+          ticketRegistry: findTicketRegistry(lotteryPda),
           systemProgram: SystemProgram.programId,
         })
         .signers([player3])
@@ -233,114 +358,95 @@ describe("lottery", () => {
       expect(lottery.ticketsSold).to.equal(3);
     });
 
-    it("Should allow authority to start the round", async () => {
-      await program.methods
-        .startRound()
-        .accounts({
-          authority: authority.publicKey,
-          lottery: lotteryPda,
-        })
-        .signers([authority])
-        .rpc();
-
-      // Verify state changed
+    it("Should close sales once every ticket is sold", async () => {
       const lottery = await program.account.lottery.fetch(lotteryPda);
-      expect(lottery.state).to.deep.equal({ waitingForRandomness: {} });
+      expect(lottery.ticketsSold).to.equal(maxTickets);
+      expect(lottery.state).to.deep.equal({ salesClosed: {} });
     });
 
-    it("Should fulfill randomness and select winner", async () => {
-      // Generate mock randomness
-      const randomness = new Uint8Array(32);
-      for (let i = 0; i < 32; i++) {
-        randomness[i] = Math.floor(Math.random() * 256);
-      }
+    it("Should let anyone record the reveal slot hash for a later reveal", async () => {
+      // The secret is mixed with the hash of a slot after sales closed, which
+      // SlotHashes only keeps for 512 slots
+      const { revealSlot, revealDeadline } = await program.account.lottery.fetch(lotteryPda);
+      expect(revealDeadline.toNumber()).to.be.greaterThan(Math.floor(Date.now() / 1000) + 60 * 60);
+      await waitForSlot(revealSlot.toNumber());
 
-      await program.methods
-        .fulfillRandomness(Array.from(randomness))
-        .accounts({
-          authority: authority.publicKey,
-          lottery: lotteryPda,
-        })
-        .signers([authority])
-        .rpc();
+      const recordSlotHash = () =>
+        program.methods
+          .recordSlotHash()
+          .accounts({
+            caller: player3.publicKey,
+            lottery: lotteryPda,
+            slotHashes: SYSVAR_SLOT_HASHES_PUBKEY,
+          })
+          .signers([player3])
+          .rpc();
 
-      // Verify randomness fulfilled
+      await recordSlotHash();
       const lottery = await program.account.lottery.fetch(lotteryPda);
-      expect(lottery.randomnessFulfilled).to.be.true;
-      expect(lottery.winner).to.not.be.null;
-      expect(lottery.winner).to.be.lessThan(3); // Should be 0, 1, or 2
-      expect(lottery.state).to.deep.equal({ waitingForPayout: {} });
+      expect(lottery.revealSlotHash).to.have.lengthOf(32);
+
+      try {
+        await recordSlotHash();
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.message).to.include("Reveal slot hash has already been recorded");
+      }
     });
 
-    it("Should close the round", async () => {
+    it("Should reveal the secret and draw a distinct winner per tier", async () => {
       await program.methods
-        .closeRound()
+        .revealRandomness(Array.from(secret))
         .accounts({
-          authority: authority.publicKey,
+          caller: authority.publicKey,
           lottery: lotteryPda,
+          slotHashes: SYSVAR_SLOT_HASHES_PUBKEY,
         })
         .signers([authority])
         .rpc();
 
-      // Verify state changed
+      // Verify randomness fulfilled
       const lottery = await program.account.lottery.fetch(lotteryPda);
-      expect(lottery.state).to.deep.equal({ closed: {} });
+      expect(lottery.randomnessFulfilled).to.be.true;
+      expect(lottery.winners).to.have.lengthOf(tieredSplit.tierBps.length);
+      expect(new Set(lottery.winners).size).to.equal(lottery.winners.length);
+      expect(lottery.winners).to.deep.equal(
+        drawDistinct(lottery.randomness, tieredSplit.tierBps.length, lottery.ticketsSold)
+      );
+      expect(lottery.state).to.deep.equal({ winnerDrawn: {} });
     });
 
-    it("Should complete payout to winner with fee distribution", async () => {
-      // Get current lottery state to find winner
+    it("Should pay the creator and platform shares", async () => {
       const lottery = await program.account.lottery.fetch(lotteryPda);
-      const winnerTicketNumber = lottery.winner;
-      
-      // Find the winning ticket PDA
-      const [winnerTicketPda] = PublicKey.findProgramAddressSync(
-        [
-          Buffer.from("ticket"), 
-          lotteryPda.toBuffer(), 
-          Buffer.from(new Uint8Array(new Uint32Array([winnerTicketNumber]).buffer))
-        ],
-        program.programId
-      );
-
-      // Get the winning ticket to find winner address
-      const winnerTicket = await program.account.ticket.fetch(winnerTicketPda);
-      const winnerAddress = winnerTicket.player;
-
-      // Create platform fee account (in real app, this would be a predetermined account)
-      const platformFeeAccount = Keypair.generate();
-
-      // Get initial balances
-      const initialWinnerBalance = await provider.connection.getBalance(winnerAddress);
       const initialCreatorBalance = await provider.connection.getBalance(authority.publicKey);
-      const initialPlatformBalance = await provider.connection.getBalance(platformFeeAccount.publicKey);
+      const initialPlatformBalance = await provider.connection.getBalance(platformFeeWallet);
 
       const totalPrizePool = lottery.totalPrizePool.toNumber();
-      const expectedWinnerAmount = Math.floor((totalPrizePool * 90) / 100);
-      const expectedCreatorAmount = Math.floor((totalPrizePool * 5) / 100);
+      const expectedWinnerAmount = Math.floor((totalPrizePool * prizeSplit.winnerBps) / 10000);
+      const expectedCreatorAmount = Math.floor((totalPrizePool * prizeSplit.creatorBps) / 10000);
       const expectedPlatformAmount = totalPrizePool - expectedWinnerAmount - expectedCreatorAmount;
 
+      payoutAccounts = {
+        caller: authority.publicKey,
+        lottery: lotteryPda,
+        lotteryCreator: authority.publicKey, // Authority is the lottery creator
+        platformFeeAccount: platformFeeWallet,
+      };
+
       await program.methods
         .payout()
-        .accounts({
-          authority: authority.publicKey,
-          lottery: lotteryPda,
-          winnerTicket: winnerTicketPda,
-          winner: winnerAddress,
-          lotteryCreator: authority.publicKey, // Authority is the lottery creator
-          platformFeeAccount: platformFeeAccount.publicKey,
-        })
+        .accounts(payoutAccounts)
         .signers([authority])
         .rpc();
 
-      // Verify payout completed
+      // Prizes are still waiting to be claimed
       const finalLottery = await program.account.lottery.fetch(lotteryPda);
-      expect(finalLottery.state).to.deep.equal({ paidOut: {} });
-      expect(finalLottery.totalPrizePool.toString()).to.equal("0");
-
-      // Verify winner received 90%
-      const finalWinnerBalance = await provider.connection.getBalance(winnerAddress);
-      const winnerReceived = finalWinnerBalance - initialWinnerBalance;
-      expect(winnerReceived).to.equal(expectedWinnerAmount);
+      expect(finalLottery.state).to.deep.equal({ winnerDrawn: {} });
+      expect(finalLottery.feesPaid).to.be.true;
+      expect(finalLottery.totalPrizePool.toNumber()).to.equal(expectedWinnerAmount);
+      expect(finalLottery.winnerPayout.toNumber()).to.equal(expectedWinnerAmount);
+      expect(finalLottery.creatorPayout.toNumber()).to.equal(expectedCreatorAmount);
+      expect(finalLottery.platformPayout.toNumber()).to.equal(expectedPlatformAmount);
 
       // Verify creator received 5%
       const finalCreatorBalance = await provider.connection.getBalance(authority.publicKey);
@@ -349,15 +455,124 @@ describe("lottery", () => {
       expect(creatorReceived).to.be.lessThan(expectedCreatorAmount + 10000);
 
       // Verify platform received 5%
-      const finalPlatformBalance = await provider.connection.getBalance(platformFeeAccount.publicKey);
-      const platformReceived = finalPlatformBalance - initialPlatformBalance;
-      expect(platformReceived).to.equal(expectedPlatformAmount);
+      const finalPlatformBalance = await provider.connection.getBalance(platformFeeWallet);
+      expect(finalPlatformBalance - initialPlatformBalance).to.equal(expectedPlatformAmount);
+    });
+
+    it("Should reject a second payout", async () => {
+      try {
+        await program.methods
+          .payout()
+          .accounts(payoutAccounts)
+          .signers([authority])
+          .rpc();
+
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.message).to.include("Lottery has already been paid out");
+      }
+    });
+
+    it("Should pay each winner their tier", async () => {
+      const lottery = await program.account.lottery.fetch(lotteryPda);
+      const winnerAmount = lottery.winnerPayout.toNumber();
+      const tierAmounts = tieredSplit.tierBps.map((bps) => Math.floor((winnerAmount * bps) / 10000));
+      tierAmounts[0] = winnerAmount - tierAmounts.slice(1).reduce((sum, amount) => sum + amount, 0);
+
+      // Resolve each winning ticket number to its owner's entry via the registry
+      const registryPda = findTicketRegistry(lotteryPda);
+      const registryInfo = await provider.connection.getAccountInfo(registryPda);
+      const registryHeaderSize = 8 + 32 + 4 + 4;
+      const entries = await program.account.playerEntry.all([
+        { memcmp: { offset: 8, bytes: lotteryPda.toBase58() } },
+      ]);
+      const players = [player1, player2, player3];
+
+      for (const [tier, ticket] of lottery.winners.entries()) {
+        const ownerIndex = registryInfo.data.readUInt32LE(registryHeaderSize + 4 * ticket);
+        const winnerEntry = entries.find(({ account }) => account.index === ownerIndex);
+        const winner = players.find((player) => player.publicKey.equals(winnerEntry.account.player));
+        // The authority pushes the first prize, the other winners pull theirs
+        const claimer = tier === 0 ? authority : winner;
+        const initialBalance = await provider.connection.getBalance(winner.publicKey);
+
+        await program.methods
+          .claimPrize(tier)
+          .accounts({
+            claimer: claimer.publicKey,
+            winner: winner.publicKey,
+            lottery: lotteryPda,
+            ticketRegistry: registryPda,
+            winnerEntry: winnerEntry.publicKey,
+          })
+          .signers([claimer])
+          .rpc();
+
+        const finalBalance = await provider.connection.getBalance(winner.publicKey);
+        expect(finalBalance - initialBalance).to.equal(tierAmounts[tier]);
+      }
+
+      const finalLottery = await program.account.lottery.fetch(lotteryPda);
+      expect(finalLottery.state).to.deep.equal({ paidOut: {} });
+      expect(finalLottery.totalPrizePool.toString()).to.equal("0");
+      expect(finalLottery.paidOutAt).to.not.be.null;
+    });
+
+    it("Should keep the lottery open until every entry is closed", async () => {
+      try {
+        await program.methods
+          .closeLottery()
+          .accounts({
+            authority: authority.publicKey,
+            lottery: lotteryPda,
+            ticketRegistry: findTicketRegistry(lotteryPda),
+          })
+          .signers([authority])
+          .rpc();
+
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.message).to.include("Player entries must be closed before the lottery");
+      }
+    });
+
+    it("Should return entry and lottery rent once settled", async () => {
+      // Entries of absent players can be closed by anyone, rent still going to the player
+      for (const player of [player1, player2, player3]) {
+        const entryPda = findPlayerEntry(lotteryPda, player.publicKey);
+        const entryRent = await provider.connection.getBalance(entryPda);
+        const initialBalance = await provider.connection.getBalance(player.publicKey);
+        const caller = player === player1 ? player1 : authority;
+
+        await program.methods
+          .closeTicket()
+          .accounts({
+            caller: caller.publicKey,
+            player: player.publicKey,
+            lottery: lotteryPda,
+            playerEntry: entryPda,
+          })
+          .signers([caller])
+          .rpc();
+
+        expect(await provider.connection.getAccountInfo(entryPda)).to.be.null;
+        const finalBalance = await provider.connection.getBalance(player.publicKey);
+        expect(finalBalance).to.be.greaterThan(initialBalance + entryRent - 10_000);
+      }
 
-      console.log(`Payout Distribution:`);
-      console.log(`Total Prize Pool: ${totalPrizePool} lamports (${totalPrizePool / LAMPORTS_PER_SOL} SOL)`);
-      console.log(`Winner (90%): ${winnerReceived} lamports (${winnerReceived / LAMPORTS_PER_SOL} SOL)`);
-      console.log(`Creator (5%): ${creatorReceived} lamports (${creatorReceived / LAMPORTS_PER_SOL} SOL)`);
-      console.log(`Platform (5%): ${platformReceived} lamports (${platformReceived / LAMPORTS_PER_SOL} SOL)`);
+      const registryPda = findTicketRegistry(lotteryPda);
+      await program.methods
+        .closeLottery()
+        .accounts({
+          authority: authority.publicKey,
+          lottery: lotteryPda,
+          ticketRegistry: registryPda,
+        })
+        .signers([authority])
+        .rpc();
+
+      expect(await provider.connection.getAccountInfo(lotteryPda)).to.be.null;
+      expect(await provider.connection.getAccountInfo(registryPda)).to.be.null;
     });
   });
 
@@ -375,7 +590,7 @@ describe("lottery", () => {
 
       // Initialize lottery for error tests
       await program.methods
-        .initializeLottery(lotteryId, ticketPrice, maxTickets)
+        .initializeLottery(lotteryId, ticketPrice, maxTickets, duration, prizeSplit, commitReveal, false)
         .accounts({
           authority: authority.publicKey,
           lottery: lotteryPda,
@@ -385,12 +600,12 @@ describe("lottery", () => {
         .rpc();
     });
 
-    it("Should fail to start round with no tickets", async () => {
+    it("Should fail to close sales while they are still open", async () => {
       try {
         await program.methods
-          .startRound()
+          .closeSales()
           .accounts({
-            authority: authority.publicKey,
+            caller: authority.publicKey,
             lottery: lotteryPda,
           })
           .signers([authority])
@@ -398,8 +613,2317 @@ describe("lottery", () => {
         
         expect.fail("Should have thrown an error");
       } catch (error) {
-        expect(error.message).to.include("No tickets have been sold");
+        expect(error.message).to.include("Ticket sales are still open");
+      }
+    });
+
+    it("Should fail to reveal before sales close", async () => {
+      try {
+        await program.methods
+          .revealRandomness(Array.from(secret))
+          .accounts({
+            caller: authority.publicKey,
+            lottery: lotteryPda,
+            slotHashes: SYSVAR_SLOT_HASHES_PUBKEY,
+          })
+          .signers([authority])
+          .rpc();
+
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.message).to.include("Ticket sales have not been closed");
+      }
+    });
+
+    it("Should reject a prize split that does not add up to 100%", async () => {
+      const otherId = lotteryId.addn(1);
+      const [otherPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("lottery"), otherId.toArrayLike(Buffer, "le", 8)],
+        program.programId
+      );
+
+      try {
+        await program.methods
+          .initializeLottery(otherId, ticketPrice, maxTickets, duration, {
+            winnerBps: 9000, creatorBps: 900, platformBps: 500, tierBps: [10000],
+          }, commitReveal, false)
+          .accounts({
+            authority: authority.publicKey,
+            lottery: otherPda,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([authority])
+          .rpc();
+
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.message).to.include("Prize shares must add up to 10000 basis points");
+      }
+    });
+
+    it("Should hold platform shares to the minimum fee, not the default", async () => {
+      const settings = await fetchPlatformSettings();
+      await program.methods.updatePlatformConfig({ ...settings, minFeeBps: 200 }).rpc();
+
+      const initialize = (offset: number, platformBps: number) => {
+        const otherId = lotteryId.addn(offset);
+        const [otherPda] = PublicKey.findProgramAddressSync(
+          [Buffer.from("lottery"), otherId.toArrayLike(Buffer, "le", 8)],
+          program.programId
+        );
+        return program.methods
+          .initializeLottery(otherId, ticketPrice, maxTickets, duration, {
+            winnerBps: 9500 - platformBps, creatorBps: 500, platformBps, tierBps: [10000],
+          }, commitReveal, false)
+          .accounts({
+            authority: authority.publicKey,
+            lottery: otherPda,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([authority])
+          .rpc();
+      };
+
+      try {
+        // Below the default fee but above the minimum
+        await initialize(2, 300);
+
+        try {
+          await initialize(3, 100);
+
+          expect.fail("Should have thrown an error");
+        } catch (error) {
+          expect(error.message).to.include("Platform share is below the minimum");
+        }
+      } finally {
+        await program.methods.updatePlatformConfig(settings).rpc();
+      }
+    });
+
+    it("Should hand the platform over only once the new admin accepts", async () => {
+      const admin = provider.wallet.publicKey;
+      const acceptAdmin = (newAdmin: Keypair) =>
+        program.methods
+          .acceptPlatformAdmin()
+          .accounts({ newAdmin: newAdmin.publicKey })
+          .signers([newAdmin])
+          .rpc();
+
+      await program.methods.transferPlatformAdmin(player1.publicKey).rpc();
+      let config = await program.account.platformConfig.fetch(platformConfigPda);
+      expect(config.admin.toString()).to.equal(admin.toString());
+      expect(config.pendingAdmin.toString()).to.equal(player1.publicKey.toString());
+
+      try {
+        await acceptAdmin(player2);
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.message).to.include("Signer is not allowed to perform this action");
+      }
+
+      await acceptAdmin(player1);
+      config = await program.account.platformConfig.fetch(platformConfigPda);
+      expect(config.admin.toString()).to.equal(player1.publicKey.toString());
+      expect(config.pendingAdmin).to.be.null;
+
+      // Hand the platform back to the provider wallet
+      await program.methods
+        .transferPlatformAdmin(admin)
+        .accounts({ admin: player1.publicKey })
+        .signers([player1])
+        .rpc();
+      await program.methods.acceptPlatformAdmin().accounts({ newAdmin: admin }).rpc();
+      config = await program.account.platformConfig.fetch(platformConfigPda);
+      expect(config.admin.toString()).to.equal(admin.toString());
+    });
+
+    it("Should block ticket sales while the lottery is paused", async () => {
+      // The provider wallet is the platform admin
+      await program.methods
+        .setLotteryPaused(true)
+        .accounts({ lottery: lotteryPda })
+        .rpc();
+
+      try {
+        await program.methods
+          .buyTicket(lotteryId)
+          .accounts({
+            player: player1.publicKey,
+            lottery: lotteryPda,
+            playerEntry: findPlayerEntry(lotteryPda, player1.publicKey),
+            ticketRegistry: findTicketRegistry(lotteryPda),
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([player1])
+          .rpc();
+
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.message).to.include("Lottery is paused");
+      }
+
+      await program.methods
+        .setLotteryPaused(false)
+        .accounts({ lottery: lotteryPda })
+        .rpc();
+    });
+
+    it("Should buy a range of tickets in one instruction", async () => {
+      const entryPda = findPlayerEntry(lotteryPda, player1.publicKey);
+
+      await program.methods
+        .buyTickets(lotteryId, 2)
+        .accounts({
+          player: player1.publicKey,
+          lottery: lotteryPda,
+          playerEntry: entryPda,
+          ticketRegistry: findTicketRegistry(lotteryPda),
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([player1])
+        .rpc();
+
+      const entry = await program.account.playerEntry.fetch(entryPda);
+      expect(entry.ticketCount).to.equal(2);
+      expect(entry.ranges).to.deep.equal([{ start: 0, count: 2 }]);
+
+      const lottery = await program.account.lottery.fetch(lotteryPda);
+      expect(lottery.ticketsSold).to.equal(2);
+      expect(lottery.totalPrizePool.toString()).to.equal(ticketPrice.muln(2).toString());
+
+      // Only one ticket is left
+      try {
+        await program.methods
+          .buyTickets(lotteryId, 2)
+          .accounts({
+            player: player2.publicKey,
+            lottery: lotteryPda,
+            playerEntry: findPlayerEntry(lotteryPda, player2.publicKey),
+            ticketRegistry: findTicketRegistry(lotteryPda),
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([player2])
+          .rpc();
+
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.message).to.include("Lottery is full");
+      }
+    });
+  });
+
+  describe("Cancellation and Refunds", () => {
+    let lotteryId: anchor.BN;
+    let lotteryPda: PublicKey;
+    let entryPda: PublicKey;
+
+    before(async () => {
+      lotteryId = new anchor.BN(Date.now() + 1);
+      [lotteryPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("lottery"), lotteryId.toArrayLike(Buffer, "le", 8)],
+        program.programId
+      );
+      entryPda = findPlayerEntry(lotteryPda, player1.publicKey);
+
+      await program.methods
+        .initializeLottery(lotteryId, ticketPrice, maxTickets, duration, prizeSplit, commitReveal, false)
+        .accounts({
+          authority: authority.publicKey,
+          lottery: lotteryPda,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([authority])
+        .rpc();
+
+      await program.methods
+        .buyTicket(lotteryId)
+        .accounts({
+          player: player1.publicKey,
+          lottery: lotteryPda,
+          playerEntry: entryPda,
+          ticketRegistry: findTicketRegistry(lotteryPda),
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([player1])
+        .rpc();
+    });
+
+    it("Should not let a stranger cancel during the grace period", async () => {
+      try {
+        await program.methods
+          .cancelLottery()
+          .accounts({ caller: player2.publicKey, lottery: lotteryPda })
+          .signers([player2])
+          .rpc();
+
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.message).to.include("Lottery cannot be cancelled by this caller yet");
+      }
+    });
+
+    it("Should let the authority cancel the lottery", async () => {
+      await program.methods
+        .cancelLottery()
+        .accounts({ caller: authority.publicKey, lottery: lotteryPda })
+        .signers([authority])
+        .rpc();
+
+      const lottery = await program.account.lottery.fetch(lotteryPda);
+      expect(lottery.state).to.deep.equal({ cancelled: {} });
+    });
+
+    it("Should refund the ticket price and close the player entry", async () => {
+      const initialBalance = await provider.connection.getBalance(player1.publicKey);
+
+      await program.methods
+        .claimRefund()
+        .accounts({
+          player: player1.publicKey,
+          lottery: lotteryPda,
+          playerEntry: entryPda,
+        })
+        .signers([player1])
+        .rpc();
+
+      const finalBalance = await provider.connection.getBalance(player1.publicKey);
+      expect(finalBalance - initialBalance).to.be.greaterThan(ticketPrice.toNumber() - 10000);
+
+      const lottery = await program.account.lottery.fetch(lotteryPda);
+      expect(lottery.totalPrizePool.toString()).to.equal("0");
+      expect(await provider.connection.getAccountInfo(entryPda)).to.be.null;
+    });
+  });
+
+  describe("Oracle Randomness", () => {
+    let lotteryId: anchor.BN;
+    let lotteryPda: PublicKey;
+
+    before(async () => {
+      lotteryId = new anchor.BN(Date.now() + 2);
+      [lotteryPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("lottery"), lotteryId.toArrayLike(Buffer, "le", 8)],
+        program.programId
+      );
+
+      await program.methods
+        .initializeLottery(lotteryId, ticketPrice, maxTickets, duration, prizeSplit, {
+          oracle: { program: mockOracle.programId },
+        }, false)
+        .accounts({
+          authority: authority.publicKey,
+          lottery: lotteryPda,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([authority])
+        .rpc();
+
+      // Sell out the lottery so sales close
+      for (const player of [player1, player2, player3]) {
+        await program.methods
+          .buyTicket(lotteryId)
+          .accounts({
+            player: player.publicKey,
+            lottery: lotteryPda,
+            playerEntry: findPlayerEntry(lotteryPda, player.publicKey),
+            ticketRegistry: findTicketRegistry(lotteryPda),
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([player])
+          .rpc();
+      }
+    });
+
+    it("Should reject an oracle the platform has not approved", async () => {
+      const otherId = new anchor.BN(Date.now() + 3);
+      const [otherPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("lottery"), otherId.toArrayLike(Buffer, "le", 8)],
+        program.programId
+      );
+
+      try {
+        await program.methods
+          .initializeLottery(otherId, ticketPrice, maxTickets, duration, prizeSplit, {
+            oracle: { program: Keypair.generate().publicKey },
+          }, false)
+          .accounts({
+            authority: authority.publicKey,
+            lottery: otherPda,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([authority])
+          .rpc();
+
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.message).to.include("Oracle program is not approved by the platform");
+      }
+    });
+
+    it("Should reject randomness from anyone but the oracle", async () => {
+      await program.methods
+        .requestRandomness()
+        .accounts({ caller: authority.publicKey, lottery: lotteryPda })
+        .signers([authority])
+        .rpc();
+
+      try {
+        await program.methods
+          .fulfillRandomness(Array(32).fill(7))
+          .accounts({ oracleAuthority: authority.publicKey, lottery: lotteryPda })
+          .signers([authority])
+          .rpc();
+
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.message).to.include("Caller is not the configured randomness oracle");
+      }
+    });
+
+    it("Should draw a winner from the oracle callback", async () => {
+      const [oracleAuthority] = PublicKey.findProgramAddressSync(
+        [Buffer.from("oracle-authority")],
+        mockOracle.programId
+      );
+
+      await mockOracle.methods
+        .fulfill(Array.from(randomBytes(32)))
+        .accounts({
+          oracleAuthority,
+          lottery: lotteryPda,
+          lotteryProgram: program.programId,
+        })
+        .rpc();
+
+      const lottery = await program.account.lottery.fetch(lotteryPda);
+      expect(lottery.state).to.deep.equal({ winnerDrawn: {} });
+      expect(lottery.winners).to.have.lengthOf(1);
+      expect(lottery.winners[0]).to.be.lessThan(maxTickets);
+    });
+  });
+
+  describe("Token Lotteries", () => {
+    const tokenPrice = new anchor.BN(1_000_000); // 1 token with 6 decimals
+    let lotteryId: anchor.BN;
+    let lotteryPda: PublicKey;
+    let vaultPda: PublicKey;
+    let mint: PublicKey;
+    let playerTokenAccount: PublicKey;
+    let creatorTokenAccount: PublicKey;
+    let platformTokenAccount: PublicKey;
+
+    before(async () => {
+      const payer = (provider.wallet as anchor.Wallet).payer;
+      mint = await createMint(provider.connection, payer, payer.publicKey, null, 6);
+
+      const ata = async (owner: PublicKey) =>
+        (await getOrCreateAssociatedTokenAccount(provider.connection, payer, mint, owner)).address;
+      playerTokenAccount = await ata(player1.publicKey);
+      creatorTokenAccount = await ata(authority.publicKey);
+      platformTokenAccount = await ata(platformFeeWallet);
+      await mintTo(provider.connection, payer, mint, playerTokenAccount, payer, 10_000_000);
+
+      lotteryId = new anchor.BN(Date.now() + 2);
+      [lotteryPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("lottery"), lotteryId.toArrayLike(Buffer, "le", 8)],
+        program.programId
+      );
+      [vaultPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("vault"), lotteryPda.toBuffer()],
+        program.programId
+      );
+    });
+
+    it("Should initialize a lottery with a token vault", async () => {
+      await program.methods
+        .initializeLottery(lotteryId, tokenPrice, maxTickets, duration, prizeSplit, commitReveal, false)
+        .accounts({
+          authority: authority.publicKey,
+          lottery: lotteryPda,
+          mint,
+          vault: vaultPda,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([authority])
+        .rpc();
+
+      const lottery = await program.account.lottery.fetch(lotteryPda);
+      expect(lottery.mint.toString()).to.equal(mint.toString());
+      const vault = await getAccount(provider.connection, vaultPda);
+      expect(vault.owner.toString()).to.equal(lotteryPda.toString());
+    });
+
+    it("Should pay for tickets from the player's token account", async () => {
+      await program.methods
+        .buyTickets(lotteryId, maxTickets)
+        .accounts({
+          player: player1.publicKey,
+          lottery: lotteryPda,
+          playerEntry: findPlayerEntry(lotteryPda, player1.publicKey),
+          ticketRegistry: findTicketRegistry(lotteryPda),
+          vault: vaultPda,
+          playerTokenAccount,
+          mint,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([player1])
+        .rpc();
+
+      const expectedPool = tokenPrice.toNumber() * maxTickets;
+      const vault = await getAccount(provider.connection, vaultPda);
+      expect(Number(vault.amount)).to.equal(expectedPool);
+      const lottery = await program.account.lottery.fetch(lotteryPda);
+      expect(lottery.totalPrizePool.toNumber()).to.equal(expectedPool);
+    });
+
+    it("Should split the vault across the winner, creator and platform", async () => {
+      const { revealSlot } = await program.account.lottery.fetch(lotteryPda);
+      await waitForSlot(revealSlot.toNumber());
+
+      await program.methods
+        .revealRandomness(Array.from(secret))
+        .accounts({
+          caller: authority.publicKey,
+          lottery: lotteryPda,
+          slotHashes: SYSVAR_SLOT_HASHES_PUBKEY,
+        })
+        .signers([authority])
+        .rpc();
+
+      const pool = tokenPrice.toNumber() * maxTickets;
+      const initialPlayerTokens = Number((await getAccount(provider.connection, playerTokenAccount)).amount);
+
+      await program.methods
+        .payout()
+        .accounts({
+          caller: authority.publicKey,
+          lottery: lotteryPda,
+          lotteryCreator: authority.publicKey,
+          platformFeeAccount: platformFeeWallet,
+          vault: vaultPda,
+          creatorTokenAccount,
+          platformTokenAccount,
+          mint,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([authority])
+        .rpc();
+
+      await program.methods
+        .claimPrize(0)
+        .accounts({
+          claimer: player1.publicKey,
+          winner: player1.publicKey,
+          lottery: lotteryPda,
+          ticketRegistry: findTicketRegistry(lotteryPda),
+          winnerEntry: findPlayerEntry(lotteryPda, player1.publicKey),
+          vault: vaultPda,
+          winnerTokenAccount: playerTokenAccount,
+          mint,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([player1])
+        .rpc();
+
+      const winnerAmount = Math.floor((pool * prizeSplit.winnerBps) / 10000);
+      const creatorAmount = Math.floor((pool * prizeSplit.creatorBps) / 10000);
+      const finalPlayerTokens = Number((await getAccount(provider.connection, playerTokenAccount)).amount);
+      expect(finalPlayerTokens - initialPlayerTokens).to.equal(winnerAmount);
+      expect(Number((await getAccount(provider.connection, creatorTokenAccount)).amount)).to.equal(creatorAmount);
+      expect(Number((await getAccount(provider.connection, vaultPda)).amount)).to.equal(0);
+    });
+
+    it("Should sweep tokens sent to the vault to the authority when closing", async () => {
+      // A donation after the pool was fixed must not keep the vault from closing
+      await transferChecked(provider.connection, player1, mint, playerTokenAccount, vaultPda, player1, 1, 6);
+
+      await program.methods
+        .closeTicket()
+        .accounts({
+          caller: player1.publicKey,
+          player: player1.publicKey,
+          lottery: lotteryPda,
+          playerEntry: findPlayerEntry(lotteryPda, player1.publicKey),
+        })
+        .signers([player1])
+        .rpc();
+
+      const initialCreatorTokens = Number((await getAccount(provider.connection, creatorTokenAccount)).amount);
+      await program.methods
+        .closeLottery()
+        .accounts({
+          authority: authority.publicKey,
+          lottery: lotteryPda,
+          ticketRegistry: findTicketRegistry(lotteryPda),
+          vault: vaultPda,
+          authorityTokenAccount: creatorTokenAccount,
+          mint,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([authority])
+        .rpc();
+
+      const finalCreatorTokens = Number((await getAccount(provider.connection, creatorTokenAccount)).amount);
+      expect(finalCreatorTokens - initialCreatorTokens).to.equal(1);
+      expect(await provider.connection.getAccountInfo(vaultPda)).to.be.null;
+      expect(await provider.connection.getAccountInfo(lotteryPda)).to.be.null;
+    });
+  });
+
+  describe("Token-2022 Lotteries", () => {
+    const tokenPrice = new anchor.BN(1_000_000);
+    const feeBps = 100; // 1% transfer fee
+
+    // Creates a Token-2022 mint with the given extensions initialized
+    const createMint2022 = async (extensions: ExtensionType[], initExtensions: (mint: PublicKey) => any[]) => {
+      const payer = (provider.wallet as anchor.Wallet).payer;
+      const mintKeypair = Keypair.generate();
+      const mintLen = getMintLen(extensions);
+      const lamports = await provider.connection.getMinimumBalanceForRentExemption(mintLen);
+      const tx = new Transaction().add(
+        SystemProgram.createAccount({
+          fromPubkey: payer.publicKey,
+          newAccountPubkey: mintKeypair.publicKey,
+          space: mintLen,
+          lamports,
+          programId: TOKEN_2022_PROGRAM_ID,
+        }),
+        ...initExtensions(mintKeypair.publicKey),
+        createInitializeMintInstruction(mintKeypair.publicKey, 6, payer.publicKey, null, TOKEN_2022_PROGRAM_ID)
+      );
+      await provider.sendAndConfirm(tx, [mintKeypair]);
+      return mintKeypair.publicKey;
+    };
+
+    const findLotteryPdas = (lotteryId: anchor.BN) => {
+      const [lotteryPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("lottery"), lotteryId.toArrayLike(Buffer, "le", 8)],
+        program.programId
+      );
+      const [vaultPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("vault"), lotteryPda.toBuffer()],
+        program.programId
+      );
+      return { lotteryPda, vaultPda };
+    };
+
+    it("Should record only what reaches the vault for transfer-fee mints", async () => {
+      const payer = (provider.wallet as anchor.Wallet).payer;
+      const mint = await createMint2022([ExtensionType.TransferFeeConfig], (mint) => [
+        createInitializeTransferFeeConfigInstruction(
+          mint,
+          payer.publicKey,
+          payer.publicKey,
+          feeBps,
+          BigInt(1_000_000_000),
+          TOKEN_2022_PROGRAM_ID
+        ),
+      ]);
+      const playerTokenAccount = (
+        await getOrCreateAssociatedTokenAccount(
+          provider.connection,
+          payer,
+          mint,
+          player1.publicKey,
+          false,
+          undefined,
+          undefined,
+          TOKEN_2022_PROGRAM_ID
+        )
+      ).address;
+      await mintTo(provider.connection, payer, mint, playerTokenAccount, payer, 10_000_000, [], undefined, TOKEN_2022_PROGRAM_ID);
+
+      const lotteryId = new anchor.BN(Date.now() + 3);
+      const { lotteryPda, vaultPda } = findLotteryPdas(lotteryId);
+      await program.methods
+        .initializeLottery(lotteryId, tokenPrice, maxTickets, duration, prizeSplit, commitReveal, false)
+        .accounts({
+          authority: authority.publicKey,
+          lottery: lotteryPda,
+          mint,
+          vault: vaultPda,
+          tokenProgram: TOKEN_2022_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([authority])
+        .rpc();
+
+      await program.methods
+        .buyTicket(lotteryId)
+        .accounts({
+          player: player1.publicKey,
+          lottery: lotteryPda,
+          playerEntry: findPlayerEntry(lotteryPda, player1.publicKey),
+          ticketRegistry: findTicketRegistry(lotteryPda),
+          vault: vaultPda,
+          playerTokenAccount,
+          mint,
+          tokenProgram: TOKEN_2022_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([player1])
+        .rpc();
+
+      const received = tokenPrice.toNumber() - (tokenPrice.toNumber() * feeBps) / 10000;
+      const vault = await getAccount(provider.connection, vaultPda, undefined, TOKEN_2022_PROGRAM_ID);
+      expect(Number(vault.amount)).to.equal(received);
+      const lottery = await program.account.lottery.fetch(lotteryPda);
+      expect(lottery.totalPrizePool.toNumber()).to.equal(received);
+      const entry = await program.account.playerEntry.fetch(findPlayerEntry(lotteryPda, player1.publicKey));
+      expect(entry.totalSpent.toNumber()).to.equal(received);
+    });
+
+    it("Should reject mints with a permanent delegate", async () => {
+      const mint = await createMint2022([ExtensionType.PermanentDelegate], (mint) => [
+        createInitializePermanentDelegateInstruction(mint, authority.publicKey, TOKEN_2022_PROGRAM_ID),
+      ]);
+      const lotteryId = new anchor.BN(Date.now() + 4);
+      const { lotteryPda, vaultPda } = findLotteryPdas(lotteryId);
+
+      try {
+        await program.methods
+          .initializeLottery(lotteryId, tokenPrice, maxTickets, duration, prizeSplit, commitReveal, false)
+          .accounts({
+            authority: authority.publicKey,
+            lottery: lotteryPda,
+            mint,
+            vault: vaultPda,
+            tokenProgram: TOKEN_2022_PROGRAM_ID,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([authority])
+          .rpc();
+
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.message).to.include("Mint has an extension that would prevent settlement");
+      }
+    });
+  });
+
+  describe("Prize Expiry", () => {
+    let lotteryPda: PublicKey;
+    let openClaimsPda: PublicKey;
+
+    const expirePrizesAccounts = (lottery: PublicKey) => ({
+      caller: player2.publicKey,
+      lottery,
+      lotteryCreator: authority.publicKey,
+      platformFeeAccount: platformFeeWallet,
+    });
+
+    // Lotteries copy the claim policy when created, so only this one is affected
+    const createDrawnLottery = async (lotteryId: anchor.BN, claimPeriod: number) => {
+      const [pda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("lottery"), lotteryId.toArrayLike(Buffer, "le", 8)],
+        program.programId
+      );
+
+      const settings = await fetchPlatformSettings();
+      await program.methods
+        .updatePlatformConfig({
+          ...settings,
+          claimPeriod: new anchor.BN(claimPeriod),
+          unclaimedPrizes: { creator: {} },
+        })
+        .rpc();
+
+      await program.methods
+        .initializeLottery(lotteryId, ticketPrice, maxTickets, duration, prizeSplit, commitReveal, false)
+        .accounts({
+          authority: authority.publicKey,
+          lottery: pda,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([authority])
+        .rpc();
+
+      await program.methods.updatePlatformConfig(settings).rpc();
+
+      await program.methods
+        .buyTickets(lotteryId, maxTickets)
+        .accounts({
+          player: player1.publicKey,
+          lottery: pda,
+          playerEntry: findPlayerEntry(pda, player1.publicKey),
+          ticketRegistry: findTicketRegistry(pda),
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([player1])
+        .rpc();
+
+      const { revealSlot } = await program.account.lottery.fetch(pda);
+      await waitForSlot(revealSlot.toNumber());
+      await program.methods
+        .revealRandomness(Array.from(secret))
+        .accounts({
+          caller: authority.publicKey,
+          lottery: pda,
+          slotHashes: SYSVAR_SLOT_HASHES_PUBKEY,
+        })
+        .signers([authority])
+        .rpc();
+
+      return pda;
+    };
+
+    before(async () => {
+      lotteryPda = await createDrawnLottery(new anchor.BN(Date.now() + 5), 1);
+      openClaimsPda = await createDrawnLottery(new anchor.BN(Date.now() + 16), 24 * 60 * 60);
+    });
+
+    it("Should not expire prizes during the claim period", async () => {
+      const lottery = await program.account.lottery.fetch(openClaimsPda);
+      expect(lottery.unclaimedPrizes).to.deep.equal({ creator: {} });
+      expect(lottery.claimDeadline.toNumber()).to.be.greaterThan(Math.floor(Date.now() / 1000) + 60 * 60);
+
+      try {
+        await program.methods
+          .expirePrizes()
+          .accounts(expirePrizesAccounts(openClaimsPda))
+          .signers([player2])
+          .rpc();
+
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.message).to.include("Prizes can still be claimed");
+      }
+
+      const unexpired = await program.account.lottery.fetch(openClaimsPda);
+      expect(unexpired.claimedTiers).to.equal(0);
+    });
+
+    it("Should send expired prizes to the creator", async () => {
+      const { claimDeadline } = await program.account.lottery.fetch(lotteryPda);
+      await waitForTimestamp(claimDeadline.toNumber());
+
+      try {
+        await program.methods
+          .claimPrize(0)
+          .accounts({
+            claimer: player1.publicKey,
+            winner: player1.publicKey,
+            lottery: lotteryPda,
+            ticketRegistry: findTicketRegistry(lotteryPda),
+            winnerEntry: findPlayerEntry(lotteryPda, player1.publicKey),
+          })
+          .signers([player1])
+          .rpc();
+
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.message).to.include("Prize claim period has ended");
+      }
+
+      const initialCreatorBalance = await provider.connection.getBalance(authority.publicKey);
+      await program.methods
+        .expirePrizes()
+        .accounts(expirePrizesAccounts(lotteryPda))
+        .signers([player2])
+        .rpc();
+
+      const expired = await program.account.lottery.fetch(lotteryPda);
+      const finalCreatorBalance = await provider.connection.getBalance(authority.publicKey);
+      expect(finalCreatorBalance - initialCreatorBalance).to.equal(expired.winnerPayout.toNumber());
+      expect(expired.claimedTiers).to.equal(1);
+      expect(expired.rolledOver.toNumber()).to.equal(0);
+      expect(expired.state).to.deep.equal({ winnerDrawn: {} });
+    });
+  });
+
+  describe("Permissionless Crank", () => {
+    const crankRewardBps = 100;
+    let lotteryId: anchor.BN;
+    let lotteryPda: PublicKey;
+
+    before(async () => {
+      lotteryId = new anchor.BN(Date.now() + 6);
+      [lotteryPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("lottery"), lotteryId.toArrayLike(Buffer, "le", 8)],
+        program.programId
+      );
+
+      // A short sale period lets the keeper close sales without selling out
+      const settings = await fetchPlatformSettings();
+      await program.methods
+        .updatePlatformConfig({ ...settings, minDuration: new anchor.BN(1), crankRewardBps })
+        .rpc();
+
+      await program.methods
+        .initializeLottery(lotteryId, ticketPrice, maxTickets, new anchor.BN(2), prizeSplit, commitReveal, false)
+        .accounts({
+          authority: authority.publicKey,
+          lottery: lotteryPda,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([authority])
+        .rpc();
+
+      await program.methods.updatePlatformConfig(settings).rpc();
+
+      await program.methods
+        .buyTickets(lotteryId, 2)
+        .accounts({
+          player: player1.publicKey,
+          lottery: lotteryPda,
+          playerEntry: findPlayerEntry(lotteryPda, player1.publicKey),
+          ticketRegistry: findTicketRegistry(lotteryPda),
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([player1])
+        .rpc();
+    });
+
+    it("Should let a keeper close sales once the lottery expires", async () => {
+      await new Promise(resolve => setTimeout(resolve, 3000));
+
+      await program.methods
+        .closeSales()
+        .accounts({ caller: player3.publicKey, lottery: lotteryPda })
+        .signers([player3])
+        .rpc();
+
+      const lottery = await program.account.lottery.fetch(lotteryPda);
+      expect(lottery.state).to.deep.equal({ salesClosed: {} });
+      expect(lottery.crankRewardBps).to.equal(crankRewardBps);
+      expect(lottery.salesCloser.toBase58()).to.equal(player3.publicKey.toBase58());
+    });
+
+    it("Should split the crank reward between the keepers of each step", async () => {
+      const { revealSlot } = await program.account.lottery.fetch(lotteryPda);
+      await waitForSlot(revealSlot.toNumber());
+      await program.methods
+        .recordSlotHash()
+        .accounts({
+          caller: player2.publicKey,
+          lottery: lotteryPda,
+          slotHashes: SYSVAR_SLOT_HASHES_PUBKEY,
+        })
+        .signers([player2])
+        .rpc();
+      await program.methods
+        .revealRandomness(Array.from(secret))
+        .accounts({
+          caller: authority.publicKey,
+          lottery: lotteryPda,
+          slotHashes: SYSVAR_SLOT_HASHES_PUBKEY,
+        })
+        .signers([authority])
+        .rpc();
+
+      const pool = (await program.account.lottery.fetch(lotteryPda)).totalPrizePool.toNumber();
+      const expectedReward = Math.floor((pool * crankRewardBps) / 10000);
+      // Closing sales, requesting the draw and settling each earn a third
+      const keeperShare = Math.floor(expectedReward / 3);
+      const initialSettlerBalance = await provider.connection.getBalance(player3.publicKey);
+      const initialDrawerBalance = await provider.connection.getBalance(player2.publicKey);
+
+      const payoutAccounts = {
+        caller: player3.publicKey,
+        lottery: lotteryPda,
+        lotteryCreator: authority.publicKey,
+        platformFeeAccount: platformFeeWallet,
+        salesCloser: player3.publicKey,
+      };
+      try {
+        await program.methods.payout().accounts(payoutAccounts).signers([player3]).rpc();
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.message).to.include("Account of a keeper owed a share of the crank reward is missing");
+      }
+
+      // The provider wallet pays the transaction fees
+      await program.methods
+        .payout()
+        .accounts({ ...payoutAccounts, drawRequester: player2.publicKey })
+        .signers([player3])
+        .rpc();
+
+      const lottery = await program.account.lottery.fetch(lotteryPda);
+      const finalSettlerBalance = await provider.connection.getBalance(player3.publicKey);
+      const finalDrawerBalance = await provider.connection.getBalance(player2.publicKey);
+      expect(finalDrawerBalance - initialDrawerBalance).to.equal(keeperShare);
+      expect(finalSettlerBalance - initialSettlerBalance).to.equal(expectedReward - keeperShare);
+      expect(lottery.crankPayout.toNumber()).to.equal(expectedReward);
+      expect(lottery.winnerPayout.toNumber()).to.equal(
+        Math.floor(((pool - expectedReward) * prizeSplit.winnerBps) / 10000)
+      );
+      expect(lottery.totalPrizePool.toNumber()).to.equal(lottery.winnerPayout.toNumber());
+    });
+  });
+
+  describe("Lottery Series", () => {
+    const seriesId = new anchor.BN(Date.now() + 7);
+    const [seriesPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("series"), seriesId.toArrayLike(Buffer, "le", 8)],
+      program.programId
+    );
+    const terms = { ticketPrice, maxTickets: 1, duration, prizeSplit, drawMode: { standard: {} } };
+    const [jackpotPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("jackpot"), seriesPda.toBuffer()],
+      program.programId
+    );
+
+    const findRound = (round: number) =>
+      PublicKey.findProgramAddressSync(
+        [Buffer.from("lottery"), seriesPda.toBuffer(), new anchor.BN(round).toArrayLike(Buffer, "le", 8)],
+        program.programId
+      )[0];
+
+    const openRound = (round: number, previousRound: PublicKey | null) =>
+      program.methods
+        .openRound(commitReveal)
+        .accounts({
+          authority: authority.publicKey,
+          series: seriesPda,
+          previousRound,
+          jackpot: jackpotPda,
+          lottery: findRound(round),
+          ticketRegistry: findTicketRegistry(findRound(round)),
+        })
+        .signers([authority])
+        .rpc();
+
+    before(async () => {
+      await program.methods
+        .initializeSeries(seriesId, terms)
+        .accounts({ authority: authority.publicKey, series: seriesPda, jackpot: jackpotPda })
+        .signers([authority])
+        .rpc();
+    });
+
+    it("Should open the first round from the series terms", async () => {
+      // Prizes of this round expire right away and roll over
+      const settings = await fetchPlatformSettings();
+      await program.methods
+        .updatePlatformConfig({ ...settings, claimPeriod: new anchor.BN(1) })
+        .rpc();
+      await openRound(0, null);
+      await program.methods.updatePlatformConfig(settings).rpc();
+
+      const series = await program.account.lotterySeries.fetch(seriesPda);
+      const round = await program.account.lottery.fetch(findRound(0));
+      expect(series.rounds.toNumber()).to.equal(1);
+      expect(series.currentRound.toString()).to.equal(findRound(0).toString());
+      expect(round.series.toString()).to.equal(seriesPda.toString());
+      expect(round.lotteryId.toNumber()).to.equal(0);
+      expect(round.maxTickets).to.equal(1);
+      expect(round.unclaimedPrizes).to.deep.equal({ rollover: {} });
+    });
+
+    it("Should not open the next round before the current one settles", async () => {
+      try {
+        await openRound(1, findRound(0));
+
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.message).to.include("The previous round has not been settled");
+      }
+    });
+
+    it("Should carry unclaimed prizes over into the next round", async () => {
+      const roundPda = findRound(0);
+      await program.methods
+        .buyTicket(new anchor.BN(0))
+        .accounts({
+          player: player1.publicKey,
+          lottery: roundPda,
+          playerEntry: findPlayerEntry(roundPda, player1.publicKey),
+          ticketRegistry: findTicketRegistry(roundPda),
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([player1])
+        .rpc();
+
+      const { revealSlot } = await program.account.lottery.fetch(roundPda);
+      await waitForSlot(revealSlot.toNumber());
+      await program.methods
+        .revealRandomness(Array.from(secret))
+        .accounts({
+          caller: authority.publicKey,
+          lottery: roundPda,
+          slotHashes: SYSVAR_SLOT_HASHES_PUBKEY,
+        })
+        .signers([authority])
+        .rpc();
+
+      const settleAccounts = {
+        caller: player2.publicKey,
+        lottery: roundPda,
+        lotteryCreator: authority.publicKey,
+        platformFeeAccount: platformFeeWallet,
+      };
+      await program.methods.payout().accounts(settleAccounts).signers([player2]).rpc();
+
+      // Nobody claims the prize before the one second claim period ends
+      await new Promise(resolve => setTimeout(resolve, 3000));
+      await program.methods.expirePrizes().accounts(settleAccounts).signers([player2]).rpc();
+
+      const settled = await program.account.lottery.fetch(roundPda);
+      expect(settled.state).to.deep.equal({ paidOut: {} });
+      expect(settled.rolledOver.toNumber()).to.equal(settled.winnerPayout.toNumber());
+
+      await openRound(1, roundPda);
+
+      const previous = await program.account.lottery.fetch(roundPda);
+      const next = await program.account.lottery.fetch(findRound(1));
+      expect(previous.rolledOver.toNumber()).to.equal(0);
+      expect(previous.nextRound.toString()).to.equal(findRound(1).toString());
+      expect(next.lotteryId.toNumber()).to.equal(1);
+      expect(next.carriedOver.toNumber()).to.equal(settled.winnerPayout.toNumber());
+      expect(next.totalPrizePool.toNumber()).to.equal(settled.winnerPayout.toNumber());
+    });
+
+    it("Should hand a cancelled round over while its refunds are unclaimed", async () => {
+      // A series whose rounds do not sell out with one ticket, so the
+      // authority can still cancel them
+      const otherSeriesId = new anchor.BN(Date.now() + 15);
+      const [otherSeries] = PublicKey.findProgramAddressSync(
+        [Buffer.from("series"), otherSeriesId.toArrayLike(Buffer, "le", 8)],
+        program.programId
+      );
+      const [otherJackpot] = PublicKey.findProgramAddressSync(
+        [Buffer.from("jackpot"), otherSeries.toBuffer()],
+        program.programId
+      );
+      const otherRound = (round: number) =>
+        PublicKey.findProgramAddressSync(
+          [Buffer.from("lottery"), otherSeries.toBuffer(), new anchor.BN(round).toArrayLike(Buffer, "le", 8)],
+          program.programId
+        )[0];
+      const openOtherRound = (round: number, previousRound: PublicKey | null) =>
+        program.methods
+          .openRound(commitReveal)
+          .accounts({
+            authority: authority.publicKey,
+            series: otherSeries,
+            previousRound,
+            jackpot: otherJackpot,
+            lottery: otherRound(round),
+            ticketRegistry: findTicketRegistry(otherRound(round)),
+          })
+          .signers([authority])
+          .rpc();
+
+      await program.methods
+        .initializeSeries(otherSeriesId, { ...terms, maxTickets: 2 })
+        .accounts({ authority: authority.publicKey, series: otherSeries, jackpot: otherJackpot })
+        .signers([authority])
+        .rpc();
+      await openOtherRound(0, null);
+
+      const roundPda = otherRound(0);
+      const entryPda = findPlayerEntry(roundPda, player2.publicKey);
+      await program.methods
+        .buyTicket(new anchor.BN(0))
+        .accounts({
+          player: player2.publicKey,
+          lottery: roundPda,
+          playerEntry: entryPda,
+          ticketRegistry: findTicketRegistry(roundPda),
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([player2])
+        .rpc();
+      await program.methods
+        .cancelLottery()
+        .accounts({ caller: authority.publicKey, lottery: roundPda })
+        .signers([authority])
+        .rpc();
+
+      await openOtherRound(1, roundPda);
+
+      // The ticket's refund stays behind in the cancelled round
+      const previous = await program.account.lottery.fetch(roundPda);
+      expect(previous.nextRound.toString()).to.equal(otherRound(1).toString());
+      expect(previous.totalPrizePool.toNumber()).to.equal(ticketPrice.toNumber());
+
+      const initialBalance = await provider.connection.getBalance(player2.publicKey);
+      await program.methods
+        .claimRefund()
+        .accounts({ player: player2.publicKey, lottery: roundPda, playerEntry: entryPda })
+        .signers([player2])
+        .rpc();
+      const finalBalance = await provider.connection.getBalance(player2.publicKey);
+      expect(finalBalance - initialBalance).to.be.greaterThan(ticketPrice.toNumber());
+    });
+  });
+
+  describe("Progressive Jackpot", () => {
+    const seriesId = new anchor.BN(Date.now() + 8);
+    const [seriesPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("series"), seriesId.toArrayLike(Buffer, "le", 8)],
+      program.programId
+    );
+    const [jackpotPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("jackpot"), seriesPda.toBuffer()],
+      program.programId
+    );
+    // Each tier draws a distinct number, so a single ticket sold can match
+    // at most one of the two tiers and the other always goes unwon
+    const terms = {
+      ticketPrice,
+      maxTickets: 1000,
+      duration: new anchor.BN(2),
+      prizeSplit: { ...prizeSplit, tierBps: [7000, 3000] },
+      drawMode: { matching: {} },
+    };
+
+    const findRound = (round: number) =>
+      PublicKey.findProgramAddressSync(
+        [Buffer.from("lottery"), seriesPda.toBuffer(), new anchor.BN(round).toArrayLike(Buffer, "le", 8)],
+        program.programId
+      )[0];
+
+    const openRound = (round: number, previousRound: PublicKey | null) =>
+      program.methods
+        .openRound(commitReveal)
+        .accounts({
+          authority: authority.publicKey,
+          series: seriesPda,
+          previousRound,
+          jackpot: jackpotPda,
+          lottery: findRound(round),
+          ticketRegistry: findTicketRegistry(findRound(round)),
+        })
+        .signers([authority])
+        .rpc();
+
+    let settings: Awaited<ReturnType<typeof fetchPlatformSettings>>;
+
+    before(async () => {
+      // Rounds last two seconds so sales end without selling out
+      settings = await fetchPlatformSettings();
+      await program.methods
+        .updatePlatformConfig({ ...settings, minDuration: new anchor.BN(1) })
+        .rpc();
+
+      await program.methods
+        .initializeSeries(seriesId, terms)
+        .accounts({ authority: authority.publicKey, series: seriesPda, jackpot: jackpotPda })
+        .signers([authority])
+        .rpc();
+      await openRound(0, null);
+    });
+
+    after(async () => {
+      await program.methods.updatePlatformConfig(settings).rpc();
+    });
+
+    it("Should move the prizes of unmatched tiers into the jackpot", async () => {
+      const roundPda = findRound(0);
+      const entryPda = findPlayerEntry(roundPda, player1.publicKey);
+      await program.methods
+        .buyTicket(new anchor.BN(0))
+        .accounts({
+          player: player1.publicKey,
+          lottery: roundPda,
+          playerEntry: entryPda,
+          ticketRegistry: findTicketRegistry(roundPda),
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([player1])
+        .rpc();
+
+      await new Promise(resolve => setTimeout(resolve, 3000));
+      await program.methods
+        .closeSales()
+        .accounts({ caller: player3.publicKey, lottery: roundPda })
+        .signers([player3])
+        .rpc();
+
+      const { revealSlot } = await program.account.lottery.fetch(roundPda);
+      await waitForSlot(revealSlot.toNumber());
+      await program.methods
+        .revealRandomness(Array.from(secret))
+        .accounts({
+          caller: authority.publicKey,
+          lottery: roundPda,
+          slotHashes: SYSVAR_SLOT_HASHES_PUBKEY,
+        })
+        .signers([authority])
+        .rpc();
+
+      const tierCount = terms.prizeSplit.tierBps.length;
+      const drawn = await program.account.lottery.fetch(roundPda);
+      expect(drawn.winners).to.deep.equal(drawDistinct(drawn.randomness, tierCount, terms.maxTickets));
+      const wonTier = drawn.winners.findIndex((ticket) => ticket < drawn.ticketsSold);
+
+      const initialJackpotBalance = await provider.connection.getBalance(jackpotPda);
+      await program.methods
+        .payout()
+        .accounts({
+          caller: player3.publicKey,
+          lottery: roundPda,
+          lotteryCreator: authority.publicKey,
+          platformFeeAccount: platformFeeWallet,
+          jackpot: jackpotPda,
+        })
+        .signers([player3])
+        .rpc();
+
+      const paid = await program.account.lottery.fetch(roundPda);
+      const winnerAmount = paid.winnerPayout.toNumber();
+      const tierAmounts = terms.prizeSplit.tierBps.map((bps) => Math.floor((winnerAmount * bps) / 10000));
+      tierAmounts[0] = winnerAmount - tierAmounts.slice(1).reduce((sum, amount) => sum + amount, 0);
+      const unwon = tierAmounts.reduce((sum, amount, tier) => (tier === wonTier ? sum : sum + amount), 0);
+
+      const jackpot = await program.account.jackpot.fetch(jackpotPda);
+      const finalJackpotBalance = await provider.connection.getBalance(jackpotPda);
+      expect(unwon).to.be.greaterThan(0);
+      expect(jackpot.amount.toNumber()).to.equal(unwon);
+      expect(finalJackpotBalance - initialJackpotBalance).to.equal(unwon);
+      expect(paid.totalPrizePool.toNumber()).to.equal(winnerAmount - unwon);
+
+      // A matched tier is still owed to the player, and the round only
+      // settles once it is claimed
+      if (wonTier >= 0) {
+        await program.methods
+          .claimPrize(wonTier)
+          .accounts({
+            claimer: player1.publicKey,
+            winner: player1.publicKey,
+            lottery: roundPda,
+            ticketRegistry: findTicketRegistry(roundPda),
+            winnerEntry: entryPda,
+          })
+          .signers([player1])
+          .rpc();
+      }
+
+      const settled = await program.account.lottery.fetch(roundPda);
+      expect(settled.state).to.deep.equal({ paidOut: {} });
+      expect(settled.totalPrizePool.toNumber()).to.equal(0);
+    });
+
+    it("Should fund the next round with the jackpot, free of fees", async () => {
+      const { amount } = await program.account.jackpot.fetch(jackpotPda);
+
+      await openRound(1, findRound(0));
+
+      const next = await program.account.lottery.fetch(findRound(1));
+      expect(next.jackpot.toNumber()).to.equal(amount.toNumber());
+      expect(next.carriedOver.toNumber()).to.equal(0);
+      expect(next.totalPrizePool.toNumber()).to.equal(amount.toNumber());
+      expect((await program.account.jackpot.fetch(jackpotPda)).amount.toNumber()).to.equal(0);
+    });
+  });
+
+  describe("Picked Numbers", () => {
+    const seriesId = new anchor.BN(Date.now() + 9);
+    const [seriesPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("series"), seriesId.toArrayLike(Buffer, "le", 8)],
+      program.programId
+    );
+    const [jackpotPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("jackpot"), seriesPda.toBuffer()],
+      program.programId
+    );
+    const [roundPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("lottery"), seriesPda.toBuffer(), new anchor.BN(0).toArrayLike(Buffer, "le", 8)],
+      program.programId
+    );
+    // Pick 3 of 1-5; three matches win 70% of the winners' share, two win 30%
+    const picks = 3;
+    const maxNumber = 5;
+    const terms = {
+      ticketPrice,
+      maxTickets: 1,
+      duration,
+      prizeSplit: { ...prizeSplit, tierBps: [7000, 3000] },
+      drawMode: { pick: { picks, maxNumber } },
+    };
+    const chosen = [3, 1, 2];
+    const claimPeriod = 4;
+
+    const [ticketPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("ticket"), roundPda.toBuffer(), new anchor.BN(0).toArrayLike(Buffer, "le", 4)],
+      program.programId
+    );
+    // Keypairs only exist once the top-level hook has run
+    const buyAccounts = () => ({
+      player: player1.publicKey,
+      lottery: roundPda,
+      playerEntry: findPlayerEntry(roundPda, player1.publicKey),
+      ticketRegistry: findTicketRegistry(roundPda),
+      systemProgram: SystemProgram.programId,
+    });
+
+    before(async () => {
+      const settings = await fetchPlatformSettings();
+      await program.methods
+        .updatePlatformConfig({ ...settings, claimPeriod: new anchor.BN(claimPeriod) })
+        .rpc();
+
+      await program.methods
+        .initializeSeries(seriesId, terms)
+        .accounts({ authority: authority.publicKey, series: seriesPda, jackpot: jackpotPda })
+        .signers([authority])
+        .rpc();
+      await program.methods
+        .openRound(commitReveal)
+        .accounts({
+          authority: authority.publicKey,
+          series: seriesPda,
+          previousRound: null,
+          jackpot: jackpotPda,
+          lottery: roundPda,
+          ticketRegistry: findTicketRegistry(roundPda),
+        })
+        .signers([authority])
+        .rpc();
+
+      await program.methods.updatePlatformConfig(settings).rpc();
+    });
+
+    it("Should only sell tickets with valid picked numbers", async () => {
+      try {
+        await program.methods
+          .buyTicket(new anchor.BN(0))
+          .accounts(buyAccounts())
+          .signers([player1])
+          .rpc();
+
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.message).to.include("Instruction does not apply to this lottery's draw mode");
+      }
+
+      try {
+        await program.methods
+          .buyNumbers(new anchor.BN(0), Buffer.from([1, 1, 2]))
+          .accounts({ ...buyAccounts(), ticket: ticketPda })
+          .signers([player1])
+          .rpc();
+
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.message).to.include("Picked numbers must be distinct");
+      }
+
+      await program.methods
+        .buyNumbers(new anchor.BN(0), Buffer.from(chosen))
+        .accounts({ ...buyAccounts(), ticket: ticketPda })
+        .signers([player1])
+        .rpc();
+
+      const ticket = await program.account.ticket.fetch(ticketPda);
+      expect(Array.from(ticket.numbers)).to.deep.equal([1, 2, 3]);
+      expect(ticket.ticketNumber).to.equal(0);
+    });
+
+    it("Should pay a ticket matching enough drawn numbers", async () => {
+      const { revealSlot } = await program.account.lottery.fetch(roundPda);
+      await waitForSlot(revealSlot.toNumber());
+      await program.methods
+        .revealRandomness(Array.from(secret))
+        .accounts({
+          caller: authority.publicKey,
+          lottery: roundPda,
+          slotHashes: SYSVAR_SLOT_HASHES_PUBKEY,
+        })
+        .signers([authority])
+        .rpc();
+
+      const drawn = await program.account.lottery.fetch(roundPda);
+      const winningNumbers = drawDistinct(drawn.randomness, picks, maxNumber).map((number) => number + 1);
+      expect(Array.from(drawn.winningNumbers)).to.deep.equal(winningNumbers);
+      const tier = picks - chosen.filter((number) => winningNumbers.includes(number)).length;
+
+      const claimNumbers = program.methods
+        .claimNumbers()
+        .accounts({ caller: player2.publicKey, lottery: roundPda, ticket: ticketPda })
+        .signers([player2]);
+      if (tier >= terms.prizeSplit.tierBps.length) {
+        try {
+          await claimNumbers.rpc();
+
+          expect.fail("Should have thrown an error");
+        } catch (error) {
+          expect(error.message).to.include("Ticket does not match enough drawn numbers");
+        }
+        return;
+      }
+
+      await claimNumbers.rpc();
+      expect((await program.account.ticket.fetch(ticketPda)).tier).to.equal(tier);
+
+      // Registered tickets collect once registration closes
+      await new Promise(resolve => setTimeout(resolve, (claimPeriod + 1) * 1000));
+      const initialBalance = await provider.connection.getBalance(player1.publicKey);
+      await program.methods
+        .collectNumbersPrize()
+        .accounts({
+          claimer: player2.publicKey,
+          winner: player1.publicKey,
+          lottery: roundPda,
+          ticketRegistry: findTicketRegistry(roundPda),
+          winnerEntry: findPlayerEntry(roundPda, player1.publicKey),
+          ticket: ticketPda,
+        })
+        .signers([player2])
+        .rpc();
+
+      const lottery = await program.account.lottery.fetch(roundPda);
+      const winnerAmount = lottery.winnerPayout.toNumber();
+      const tierAmounts = terms.prizeSplit.tierBps.map((bps) => Math.floor((winnerAmount * bps) / 10000));
+      tierAmounts[0] = winnerAmount - tierAmounts.slice(1).reduce((sum, amount) => sum + amount, 0);
+      const finalBalance = await provider.connection.getBalance(player1.publicKey);
+      // The winner also gets the ticket account's rent back
+      expect(finalBalance - initialBalance).to.be.greaterThan(tierAmounts[tier]);
+      expect(lottery.tierWinners[tier]).to.equal(1);
+      expect(lottery.tierPayments[tier]).to.equal(1);
+      expect(await provider.connection.getAccountInfo(ticketPda)).to.be.null;
+
+      // The other tier had no winner and goes to the jackpot at settlement
+      await program.methods
+        .payout()
+        .accounts({
+          caller: player2.publicKey,
+          lottery: roundPda,
+          lotteryCreator: authority.publicKey,
+          platformFeeAccount: platformFeeWallet,
+          jackpot: jackpotPda,
+        })
+        .signers([player2])
+        .rpc();
+
+      const settled = await program.account.lottery.fetch(roundPda);
+      const jackpot = await program.account.jackpot.fetch(jackpotPda);
+      expect(settled.state).to.deep.equal({ paidOut: {} });
+      expect(jackpot.amount.toNumber()).to.equal(tierAmounts[1 - tier]);
+    });
+
+    it("Should close the picked tickets of a refunded entry", async () => {
+      const otherSeriesId = new anchor.BN(Date.now() + 14);
+      const [otherSeries] = PublicKey.findProgramAddressSync(
+        [Buffer.from("series"), otherSeriesId.toArrayLike(Buffer, "le", 8)],
+        program.programId
+      );
+      const [otherJackpot] = PublicKey.findProgramAddressSync(
+        [Buffer.from("jackpot"), otherSeries.toBuffer()],
+        program.programId
+      );
+      const [otherRound] = PublicKey.findProgramAddressSync(
+        [Buffer.from("lottery"), otherSeries.toBuffer(), new anchor.BN(0).toArrayLike(Buffer, "le", 8)],
+        program.programId
+      );
+      const [ticket0, ticket1, otherTicket] = [0, 1, 2].map((number) =>
+        PublicKey.findProgramAddressSync(
+          [Buffer.from("ticket"), otherRound.toBuffer(), new anchor.BN(number).toArrayLike(Buffer, "le", 4)],
+          program.programId
+        )[0]
+      );
+      const tickets = [ticket0, ticket1];
+      const entryPda = findPlayerEntry(otherRound, player1.publicKey);
+
+      await program.methods
+        .initializeSeries(otherSeriesId, { ...terms, maxTickets: 3 })
+        .accounts({ authority: authority.publicKey, series: otherSeries, jackpot: otherJackpot })
+        .signers([authority])
+        .rpc();
+      await program.methods
+        .openRound(commitReveal)
+        .accounts({
+          authority: authority.publicKey,
+          series: otherSeries,
+          previousRound: null,
+          jackpot: otherJackpot,
+          lottery: otherRound,
+          ticketRegistry: findTicketRegistry(otherRound),
+        })
+        .signers([authority])
+        .rpc();
+      for (const ticket of tickets) {
+        await program.methods
+          .buyNumbers(new anchor.BN(0), Buffer.from(chosen))
+          .accounts({
+            ...buyAccounts(),
+            lottery: otherRound,
+            playerEntry: entryPda,
+            ticketRegistry: findTicketRegistry(otherRound),
+            ticket,
+          })
+          .signers([player1])
+          .rpc();
+      }
+      await program.methods
+        .buyNumbers(new anchor.BN(0), Buffer.from(chosen))
+        .accounts({
+          ...buyAccounts(),
+          player: player2.publicKey,
+          lottery: otherRound,
+          playerEntry: findPlayerEntry(otherRound, player2.publicKey),
+          ticketRegistry: findTicketRegistry(otherRound),
+          ticket: otherTicket,
+        })
+        .signers([player2])
+        .rpc();
+
+      await program.methods
+        .cancelLottery()
+        .accounts({ caller: authority.publicKey, lottery: otherRound })
+        .signers([authority])
+        .rpc();
+
+      // Only tickets held by the entry can be closed through it
+      try {
+        await program.methods
+          .claimRefund()
+          .accounts({ player: player1.publicKey, lottery: otherRound, playerEntry: entryPda })
+          .remainingAccounts([{ pubkey: otherTicket, isWritable: true, isSigner: false }])
+          .signers([player1])
+          .rpc();
+
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.message).to.include("Ticket is not held by this entry");
+      }
+
+      await program.methods
+        .claimRefund()
+        .accounts({ player: player1.publicKey, lottery: otherRound, playerEntry: entryPda })
+        .remainingAccounts(tickets.map((pubkey) => ({ pubkey, isWritable: true, isSigner: false })))
+        .signers([player1])
+        .rpc();
+
+      expect(await provider.connection.getAccountInfo(entryPda)).to.be.null;
+      for (const ticket of tickets) {
+        expect(await provider.connection.getAccountInfo(ticket)).to.be.null;
+      }
+    });
+  });
+
+  describe("Ticket Transfers", () => {
+    const lotteryId = new anchor.BN(Date.now() + 10);
+    const [lotteryPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("lottery"), lotteryId.toArrayLike(Buffer, "le", 8)],
+      program.programId
+    );
+    // Keypairs only exist once the top-level hook has run
+    const transferAccounts = () => ({
+      holder: player1.publicKey,
+      lottery: lotteryPda,
+      holderEntry: findPlayerEntry(lotteryPda, player1.publicKey),
+      recipientEntry: findPlayerEntry(lotteryPda, player2.publicKey),
+      ticketRegistry: findTicketRegistry(lotteryPda),
+      systemProgram: SystemProgram.programId,
+    });
+
+    before(async () => {
+      await program.methods
+        .initializeLottery(lotteryId, ticketPrice, maxTickets, duration, prizeSplit, commitReveal, false)
+        .accounts({
+          authority: authority.publicKey,
+          lottery: lotteryPda,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([authority])
+        .rpc();
+
+      await program.methods
+        .buyTickets(lotteryId, 3)
+        .accounts({
+          player: player1.publicKey,
+          lottery: lotteryPda,
+          playerEntry: findPlayerEntry(lotteryPda, player1.publicKey),
+          ticketRegistry: findTicketRegistry(lotteryPda),
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([player1])
+        .rpc();
+    });
+
+    it("Should let a player buy a ticket as a gift", async () => {
+      const recipientEntry = findPlayerEntry(lotteryPda, player3.publicKey);
+      const initialBalance = await provider.connection.getBalance(player3.publicKey);
+
+      await program.methods
+        .buyTicketFor(lotteryId, player3.publicKey)
+        .accounts({
+          buyer: player1.publicKey,
+          lottery: lotteryPda,
+          recipientEntry,
+          ticketRegistry: findTicketRegistry(lotteryPda),
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([player1])
+        .rpc();
+
+      const entry = await program.account.playerEntry.fetch(recipientEntry);
+      expect(entry.player.toString()).to.equal(player3.publicKey.toString());
+      expect(entry.ticketCount).to.equal(1);
+      expect(entry.ranges[0].start).to.equal(3);
+      expect(entry.totalSpent.toString()).to.equal(ticketPrice.toString());
+      // The buyer pays for both the ticket and the recipient's entry
+      expect(await provider.connection.getBalance(player3.publicKey)).to.equal(initialBalance);
+    });
+
+    it("Should transfer a ticket along with its price", async () => {
+      await program.methods
+        .transferTicket(1, player2.publicKey)
+        .accounts(transferAccounts())
+        .signers([player1])
+        .rpc();
+
+      const holder = await program.account.playerEntry.fetch(transferAccounts().holderEntry);
+      const recipient = await program.account.playerEntry.fetch(transferAccounts().recipientEntry);
+      expect(holder.ticketCount).to.equal(2);
+      expect(holder.ranges.map((range) => [range.start, range.count])).to.deep.equal([[0, 1], [2, 1]]);
+      expect(holder.totalSpent.toNumber()).to.equal(2 * ticketPrice.toNumber());
+      expect(recipient.ticketCount).to.equal(1);
+      expect(recipient.ranges.map((range) => [range.start, range.count])).to.deep.equal([[1, 1]]);
+      expect(recipient.totalSpent.toString()).to.equal(ticketPrice.toString());
+    });
+
+    it("Should only let the holder transfer a ticket", async () => {
+      try {
+        await program.methods
+          .transferTicket(1, player2.publicKey)
+          .accounts(transferAccounts())
+          .signers([player1])
+          .rpc();
+
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.message).to.include("Ticket is not held by this entry");
+      }
+    });
+
+    it("Should only stop transfers at sales close when the lottery locks them", async () => {
+      // Selling every ticket closes sales
+      const sellOut = async (lotteryId: anchor.BN, lockedAtClose: boolean) => {
+        const [pda] = PublicKey.findProgramAddressSync(
+          [Buffer.from("lottery"), lotteryId.toArrayLike(Buffer, "le", 8)],
+          program.programId
+        );
+        await program.methods
+          .initializeLottery(lotteryId, ticketPrice, maxTickets, duration, prizeSplit, commitReveal, false)
+          .accounts({
+            authority: authority.publicKey,
+            lottery: pda,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([authority])
+          .rpc();
+        await program.methods
+          .setTransferLock(lockedAtClose)
+          .accounts({ authority: authority.publicKey, lottery: pda })
+          .signers([authority])
+          .rpc();
+        await program.methods
+          .buyTickets(lotteryId, maxTickets)
+          .accounts({
+            player: player1.publicKey,
+            lottery: pda,
+            playerEntry: findPlayerEntry(pda, player1.publicKey),
+            ticketRegistry: findTicketRegistry(pda),
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([player1])
+          .rpc();
+        expect((await program.account.lottery.fetch(pda)).state).to.deep.equal({ salesClosed: {} });
+        return pda;
+      };
+
+      const transferFirstTicket = (pda: PublicKey) =>
+        program.methods
+          .transferTicket(0, player2.publicKey)
+          .accounts({
+            holder: player1.publicKey,
+            lottery: pda,
+            holderEntry: findPlayerEntry(pda, player1.publicKey),
+            recipientEntry: findPlayerEntry(pda, player2.publicKey),
+            ticketRegistry: findTicketRegistry(pda),
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([player1])
+          .rpc();
+
+      const unlockedPda = await sellOut(new anchor.BN(Date.now() + 17), false);
+      await transferFirstTicket(unlockedPda);
+      const recipient = await program.account.playerEntry.fetch(findPlayerEntry(unlockedPda, player2.publicKey));
+      expect(recipient.ticketCount).to.equal(1);
+
+      const lockedPda = await sellOut(new anchor.BN(Date.now() + 18), true);
+      try {
+        await transferFirstTicket(lockedPda);
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.message).to.include("Tickets of this lottery can no longer be transferred");
+      }
+    });
+
+    it("Should stop transfers once the lottery is cancelled and refund the new holder", async () => {
+      await program.methods
+        .setTransferLock(true)
+        .accounts({ authority: authority.publicKey, lottery: lotteryPda })
+        .signers([authority])
+        .rpc();
+      expect((await program.account.lottery.fetch(lotteryPda)).transfersLockedAtClose).to.be.true;
+
+      await program.methods
+        .cancelLottery()
+        .accounts({ caller: authority.publicKey, lottery: lotteryPda })
+        .signers([authority])
+        .rpc();
+
+      try {
+        await program.methods
+          .transferTicket(0, player2.publicKey)
+          .accounts(transferAccounts())
+          .signers([player1])
+          .rpc();
+
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.message).to.include("Tickets of this lottery can no longer be transferred");
+      }
+
+      const initialBalance = await provider.connection.getBalance(player2.publicKey);
+      await program.methods
+        .claimRefund()
+        .accounts({
+          player: player2.publicKey,
+          lottery: lotteryPda,
+          playerEntry: transferAccounts().recipientEntry,
+        })
+        .signers([player2])
+        .rpc();
+
+      const finalBalance = await provider.connection.getBalance(player2.publicKey);
+      expect(finalBalance - initialBalance).to.be.greaterThan(ticketPrice.toNumber() - 10000);
+    });
+  });
+
+  describe("Ticket NFTs", () => {
+    const lotteryId = new anchor.BN(Date.now() + 11);
+    const [lotteryPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("lottery"), lotteryId.toArrayLike(Buffer, "le", 8)],
+      program.programId
+    );
+    const [ticketMint] = PublicKey.findProgramAddressSync(
+      [Buffer.from("ticket-mint"), lotteryPda.toBuffer(), new anchor.BN(0).toArrayLike(Buffer, "le", 4)],
+      program.programId
+    );
+    // Keypairs only exist once the top-level hook has run
+    const buyAccounts = () => ({
+      player: player1.publicKey,
+      lottery: lotteryPda,
+      playerEntry: findPlayerEntry(lotteryPda, player1.publicKey),
+      ticketRegistry: findTicketRegistry(lotteryPda),
+      systemProgram: SystemProgram.programId,
+    });
+
+    before(async () => {
+      await program.methods
+        .initializeLottery(lotteryId, ticketPrice, 1, duration, prizeSplit, commitReveal, true)
+        .accounts({
+          authority: authority.publicKey,
+          lottery: lotteryPda,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([authority])
+        .rpc();
+    });
+
+    it("Should mint an NFT for every ticket bought", async () => {
+      try {
+        await program.methods
+          .buyTicket(lotteryId)
+          .accounts(buyAccounts())
+          .signers([player1])
+          .rpc();
+
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.message).to.include("NFT lotteries sell one ticket at a time with its NFT accounts");
+      }
+
+      const ticketTokenAccount = getAssociatedTokenAddressSync(
+        ticketMint,
+        player1.publicKey,
+        false,
+        TOKEN_2022_PROGRAM_ID
+      );
+      await program.methods
+        .buyTicket(lotteryId)
+        .accounts({
+          ...buyAccounts(),
+          ticketMint,
+          ticketTokenAccount,
+          ticketTokenProgram: TOKEN_2022_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        })
+        .signers([player1])
+        .rpc();
+
+      const mint = await getMint(provider.connection, ticketMint, "confirmed", TOKEN_2022_PROGRAM_ID);
+      expect(mint.supply.toString()).to.equal("1");
+      expect(mint.decimals).to.equal(0);
+      expect(mint.mintAuthority).to.be.null;
+      const metadata = await getTokenMetadata(provider.connection, ticketMint, "confirmed", TOKEN_2022_PROGRAM_ID);
+      expect(metadata.name).to.equal(`Lottery ${lotteryId.toString()} Ticket #0`);
+      const account = await getAccount(provider.connection, ticketTokenAccount, "confirmed", TOKEN_2022_PROGRAM_ID);
+      expect(account.amount.toString()).to.equal("1");
+    });
+
+    it("Should pay the prize to whoever holds the winning ticket NFT", async () => {
+      // The ticket changes hands like any token
+      const source = getAssociatedTokenAddressSync(ticketMint, player1.publicKey, false, TOKEN_2022_PROGRAM_ID);
+      const holder = await getOrCreateAssociatedTokenAccount(
+        provider.connection,
+        player2,
+        ticketMint,
+        player2.publicKey,
+        false,
+        "confirmed",
+        undefined,
+        TOKEN_2022_PROGRAM_ID
+      );
+      await transferChecked(
+        provider.connection,
+        player1,
+        source,
+        ticketMint,
+        holder.address,
+        player1,
+        1,
+        0,
+        [],
+        undefined,
+        TOKEN_2022_PROGRAM_ID
+      );
+
+      const { revealSlot } = await program.account.lottery.fetch(lotteryPda);
+      await waitForSlot(revealSlot.toNumber());
+      await program.methods
+        .revealRandomness(Array.from(secret))
+        .accounts({
+          caller: authority.publicKey,
+          lottery: lotteryPda,
+          slotHashes: SYSVAR_SLOT_HASHES_PUBKEY,
+        })
+        .signers([authority])
+        .rpc();
+
+      // The buyer's entry no longer proves anything
+      try {
+        await program.methods
+          .claimPrize(0)
+          .accounts({
+            claimer: player1.publicKey,
+            winner: player1.publicKey,
+            lottery: lotteryPda,
+            ticketRegistry: findTicketRegistry(lotteryPda),
+            winnerEntry: findPlayerEntry(lotteryPda, player1.publicKey),
+          })
+          .signers([player1])
+          .rpc();
+
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.message).to.include("Token account does not hold the winning ticket NFT");
+      }
+
+      const initialBalance = await provider.connection.getBalance(player2.publicKey);
+      await program.methods
+        .claimPrize(0)
+        .accounts({
+          claimer: player2.publicKey,
+          winner: player2.publicKey,
+          lottery: lotteryPda,
+          ticketRegistry: findTicketRegistry(lotteryPda),
+          winnerEntry: null,
+          ticketTokenAccount: holder.address,
+        })
+        .signers([player2])
+        .rpc();
+
+      const lottery = await program.account.lottery.fetch(lotteryPda);
+      const finalBalance = await provider.connection.getBalance(player2.publicKey);
+      expect(finalBalance - initialBalance).to.equal(lottery.winnerPayout.toNumber());
+    });
+
+    it("Should refund a cancelled ticket to whoever holds its NFT", async () => {
+      const refundId = lotteryId.addn(1);
+      const [refundPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("lottery"), refundId.toArrayLike(Buffer, "le", 8)],
+        program.programId
+      );
+      const [refundMint] = PublicKey.findProgramAddressSync(
+        [Buffer.from("ticket-mint"), refundPda.toBuffer(), new anchor.BN(0).toArrayLike(Buffer, "le", 4)],
+        program.programId
+      );
+      const buyerEntry = findPlayerEntry(refundPda, player1.publicKey);
+      const buyerTokenAccount = getAssociatedTokenAddressSync(refundMint, player1.publicKey, false, TOKEN_2022_PROGRAM_ID);
+
+      await program.methods
+        .initializeLottery(refundId, ticketPrice, 2, duration, prizeSplit, commitReveal, true)
+        .accounts({
+          authority: authority.publicKey,
+          lottery: refundPda,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([authority])
+        .rpc();
+      await program.methods
+        .buyTicket(refundId)
+        .accounts({
+          player: player1.publicKey,
+          lottery: refundPda,
+          playerEntry: buyerEntry,
+          ticketRegistry: findTicketRegistry(refundPda),
+          ticketMint: refundMint,
+          ticketTokenAccount: buyerTokenAccount,
+          ticketTokenProgram: TOKEN_2022_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([player1])
+        .rpc();
+
+      // The buyer sells the ticket, then the authority cancels
+      const holder = await getOrCreateAssociatedTokenAccount(
+        provider.connection,
+        player2,
+        refundMint,
+        player2.publicKey,
+        false,
+        "confirmed",
+        undefined,
+        TOKEN_2022_PROGRAM_ID
+      );
+      await transferChecked(
+        provider.connection,
+        player1,
+        buyerTokenAccount,
+        refundMint,
+        holder.address,
+        player1,
+        1,
+        0,
+        [],
+        undefined,
+        TOKEN_2022_PROGRAM_ID
+      );
+      await program.methods
+        .cancelLottery()
+        .accounts({ caller: authority.publicKey, lottery: refundPda })
+        .signers([authority])
+        .rpc();
+
+      const closeBuyerEntry = () =>
+        program.methods
+          .closeTicket()
+          .accounts({
+            caller: player1.publicKey,
+            player: player1.publicKey,
+            lottery: refundPda,
+            playerEntry: buyerEntry,
+          })
+          .signers([player1])
+          .rpc();
+
+      // The buyer's entry no longer holds the ticket's refund
+      try {
+        await program.methods
+          .claimRefund()
+          .accounts({ player: player1.publicKey, lottery: refundPda, playerEntry: buyerEntry })
+          .signers([player1])
+          .rpc();
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.message).to.include("Tickets of an NFT lottery are refunded to their NFT holders");
+      }
+      try {
+        await closeBuyerEntry();
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.message).to.include("Ticket NFTs bought through this entry have not all been refunded");
+      }
+
+      const initialBalance = await provider.connection.getBalance(player2.publicKey);
+      await program.methods
+        .claimNftRefund(0)
+        .accounts({
+          holder: player2.publicKey,
+          lottery: refundPda,
+          ticketRegistry: findTicketRegistry(refundPda),
+          buyerEntry,
+          ticketMint: refundMint,
+          ticketTokenAccount: holder.address,
+          ticketTokenProgram: TOKEN_2022_PROGRAM_ID,
+        })
+        .signers([player2])
+        .rpc();
+
+      const finalBalance = await provider.connection.getBalance(player2.publicKey);
+      expect(finalBalance - initialBalance).to.be.greaterThan(ticketPrice.toNumber() - 10_000);
+      const mint = await getMint(provider.connection, refundMint, "confirmed", TOKEN_2022_PROGRAM_ID);
+      expect(mint.supply.toString()).to.equal("0");
+      const lottery = await program.account.lottery.fetch(refundPda);
+      expect(lottery.totalPrizePool.toNumber()).to.equal(0);
+
+      // With every NFT refunded the buyer gets the entry's rent back
+      await closeBuyerEntry();
+      expect(await provider.connection.getAccountInfo(buyerEntry)).to.be.null;
+    });
+  });
+
+  describe("Compressed Tickets", () => {
+    const compressionProgram = new PublicKey("cmtDvXumGCrqC1Age74AVPhSRVXJMd8PJS91L8KbNCK");
+    const noopProgram = new PublicKey("noopb9bkMVfRPU8AsbpTUg8AQkHtKwMYZiFUjNRtMmV");
+    const maxDepth = 3;
+    const maxBufferSize = 8;
+    // Header, then the sequence number, active index and buffer size, a
+    // change log per buffer entry and the rightmost path, without a canopy
+    const pathSize = 40 + 32 * maxDepth;
+    const treeSize = 56 + 24 + maxBufferSize * pathSize + pathSize;
+
+    // Leaves that fail to prove are rejected by the compression program,
+    // whose error shows up in the logs of the failed transaction
+    const expectTreeError = (error: any) =>
+      expect((error.logs ?? []).join("\n")).to.include("Error Code: ConcurrentMerkleTreeError");
+
+    const keccak = (...parts: Buffer[]) => Buffer.from(keccak_256(Buffer.concat(parts)));
+
+    // Off-chain replica of `compression::ticket_leaf`
+    const ticketLeaf = (lottery: PublicKey, player: PublicKey, ticket: number, purchasedAt: anchor.BN) =>
+      keccak(
+        lottery.toBuffer(),
+        player.toBuffer(),
+        new anchor.BN(ticket).toArrayLike(Buffer, "le", 4),
+        purchasedAt.toArrayLike(Buffer, "le", 8)
+      );
+
+    // Root of a tree holding `leaves`, and the proof of the leaf at `index`
+    const proveLeaf = (leaves: Buffer[], index: number) => {
+      let level = [...leaves];
+      while (level.length < 1 << maxDepth) level.push(Buffer.alloc(32));
+      const proof: PublicKey[] = [];
+      for (let depth = 0; depth < maxDepth; depth++) {
+        proof.push(new PublicKey(level[index ^ 1]));
+        const next: Buffer[] = [];
+        for (let i = 0; i < level.length; i += 2) next.push(keccak(level[i], level[i + 1]));
+        level = next;
+        index >>= 1;
+      }
+      return {
+        root: Array.from(level[0]),
+        proof: proof.map((pubkey) => ({ pubkey, isWritable: false, isSigner: false })),
+      };
+    };
+
+    const openCompressedLottery = async (lotteryId: anchor.BN) => {
+      const [lotteryPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("lottery"), lotteryId.toArrayLike(Buffer, "le", 8)],
+        program.programId
+      );
+      const merkleTree = Keypair.generate();
+
+      await program.methods
+        .initializeLottery(lotteryId, ticketPrice, maxTickets, duration, prizeSplit, commitReveal, false)
+        .accounts({
+          authority: authority.publicKey,
+          lottery: lotteryPda,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([authority])
+        .rpc();
+
+      const allocTree = SystemProgram.createAccount({
+        fromPubkey: authority.publicKey,
+        newAccountPubkey: merkleTree.publicKey,
+        lamports: await provider.connection.getMinimumBalanceForRentExemption(treeSize),
+        space: treeSize,
+        programId: compressionProgram,
+      });
+      await program.methods
+        .initTicketTree(maxDepth, maxBufferSize)
+        .accounts({
+          authority: authority.publicKey,
+          lottery: lotteryPda,
+          merkleTree: merkleTree.publicKey,
+          compressionProgram,
+          noopProgram,
+        })
+        .preInstructions([allocTree])
+        .signers([authority, merkleTree])
+        .rpc();
+
+      return { lotteryPda, merkleTree: merkleTree.publicKey };
+    };
+
+    // Buys compressed tickets and returns their leaves, rebuilt from the event
+    const buyCompressed = async (lotteryId: anchor.BN, lotteryPda: PublicKey, merkleTree: PublicKey, player: Keypair, count: number) => {
+      const signature = await program.methods
+        .buyCompressedTickets(lotteryId, count)
+        .accounts({
+          player: player.publicKey,
+          lottery: lotteryPda,
+          merkleTree,
+          compressionProgram,
+          noopProgram,
+        })
+        .signers([player])
+        .rpc({ commitment: "confirmed" });
+
+      const tx = await provider.connection.getTransaction(signature, {
+        commitment: "confirmed",
+        maxSupportedTransactionVersion: 0,
+      });
+      const parser = new anchor.EventParser(program.programId, new anchor.BorshCoder(program.idl));
+      const event = Array.from(parser.parseLogs(tx.meta.logMessages)).find(
+        (event) => event.name === "compressedTicketsBought"
+      );
+      const { firstTicket, purchasedAt } = event.data as { firstTicket: number; purchasedAt: anchor.BN };
+      return Array.from({ length: count }, (_, offset) => ({
+        player,
+        purchasedAt,
+        leaf: ticketLeaf(lotteryPda, player.publicKey, firstTicket + offset, purchasedAt),
+      }));
+    };
+
+    it("Should pay the prize to the player proving the winning ticket", async () => {
+      const lotteryId = new anchor.BN(Date.now() + 12);
+      const { lotteryPda, merkleTree } = await openCompressedLottery(lotteryId);
+
+      try {
+        await program.methods
+          .buyTicket(lotteryId)
+          .accounts({
+            player: player3.publicKey,
+            lottery: lotteryPda,
+            playerEntry: findPlayerEntry(lotteryPda, player3.publicKey),
+            ticketRegistry: findTicketRegistry(lotteryPda),
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([player3])
+          .rpc();
+
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.message).to.include("Instruction does not apply to how this lottery stores its tickets");
+      }
+
+      const tickets = [
+        ...(await buyCompressed(lotteryId, lotteryPda, merkleTree, player1, 2)),
+        ...(await buyCompressed(lotteryId, lotteryPda, merkleTree, player2, 1)),
+      ];
+      const leaves = tickets.map((ticket) => ticket.leaf);
+
+      const { revealSlot } = await program.account.lottery.fetch(lotteryPda);
+      await waitForSlot(revealSlot.toNumber());
+      await program.methods
+        .revealRandomness(Array.from(secret))
+        .accounts({
+          caller: authority.publicKey,
+          lottery: lotteryPda,
+          slotHashes: SYSVAR_SLOT_HASHES_PUBKEY,
+        })
+        .signers([authority])
+        .rpc();
+
+      const [winningTicket] = (await program.account.lottery.fetch(lotteryPda)).winners;
+      const winner = tickets[winningTicket];
+      const { root, proof } = proveLeaf(leaves, winningTicket);
+      const claimAccounts = {
+        claimer: winner.player.publicKey,
+        winner: winner.player.publicKey,
+        lottery: lotteryPda,
+        merkleTree,
+        compressionProgram,
+        noopProgram,
+      };
+
+      // A leaf with any other field fails the compression program's proof check
+      try {
+        await program.methods
+          .claimCompressedPrize(0, root, winner.purchasedAt.addn(1))
+          .accounts(claimAccounts)
+          .remainingAccounts(proof)
+          .signers([winner.player])
+          .rpc();
+
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expectTreeError(error);
+      }
+
+      const initialBalance = await provider.connection.getBalance(winner.player.publicKey);
+      await program.methods
+        .claimCompressedPrize(0, root, winner.purchasedAt)
+        .accounts(claimAccounts)
+        .remainingAccounts(proof)
+        .signers([winner.player])
+        .rpc();
+
+      let lottery = await program.account.lottery.fetch(lotteryPda);
+      const finalBalance = await provider.connection.getBalance(winner.player.publicKey);
+      expect(finalBalance - initialBalance).to.equal(lottery.winnerPayout.toNumber());
+      // The creator and platform shares are still owed
+      expect(lottery.state).to.deep.equal({ winnerDrawn: {} });
+
+      await program.methods
+        .payout()
+        .accounts({
+          caller: authority.publicKey,
+          lottery: lotteryPda,
+          lotteryCreator: authority.publicKey,
+          platformFeeAccount: platformFeeWallet,
+        })
+        .signers([authority])
+        .rpc();
+
+      lottery = await program.account.lottery.fetch(lotteryPda);
+      expect(lottery.state).to.deep.equal({ paidOut: {} });
+    });
+
+    it("Should refund a compressed ticket of a cancelled lottery only once", async () => {
+      const lotteryId = new anchor.BN(Date.now() + 13);
+      const { lotteryPda, merkleTree } = await openCompressedLottery(lotteryId);
+      const [ticket] = await buyCompressed(lotteryId, lotteryPda, merkleTree, player1, 1);
+
+      await program.methods
+        .cancelLottery()
+        .accounts({ caller: authority.publicKey, lottery: lotteryPda })
+        .signers([authority])
+        .rpc();
+
+      const { root, proof } = proveLeaf([ticket.leaf], 0);
+      const refund = () =>
+        program.methods
+          .claimCompressedRefund(0, root, ticket.purchasedAt)
+          .accounts({
+            caller: player2.publicKey,
+            player: player1.publicKey,
+            lottery: lotteryPda,
+            merkleTree,
+            compressionProgram,
+            noopProgram,
+          })
+          .remainingAccounts(proof)
+          .signers([player2])
+          .rpc();
+
+      const initialBalance = await provider.connection.getBalance(player1.publicKey);
+      await refund();
+      const finalBalance = await provider.connection.getBalance(player1.publicKey);
+      expect(finalBalance - initialBalance).to.equal(ticketPrice.toNumber());
+      expect((await program.account.lottery.fetch(lotteryPda)).totalPrizePool.toString()).to.equal("0");
+
+      // The refunded leaf was replaced by the empty leaf, so it no longer
+      // matches the ticket being proven
+      try {
+        await refund();
+
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expectTreeError(error);
       }
     });
   });
-});
\ No newline at end of file
+});